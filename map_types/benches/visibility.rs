@@ -0,0 +1,20 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use map_types::*;
+
+/// An open field is the worst case, every line runs all the way to its tile.
+fn field_of_view_by_radius(c: &mut Criterion) {
+    let (square, hex) = (Obstacles::new(1), Obstacles::new(1));
+    let mut group = c.benchmark_group("field_of_view");
+    for radius in [4, 8, MAX_VIEW_RADIUS] {
+        group.bench_with_input(BenchmarkId::new("square", radius), &radius, |b, &r| {
+            b.iter(|| field_of_view(&Position4 { x: 0, y: 0 }, r, &square, Vision::Symmetric))
+        });
+        group.bench_with_input(BenchmarkId::new("hex", radius), &radius, |b, &r| {
+            b.iter(|| field_of_view(&Position6Axial { x: 0, y: 0 }, r, &hex, Vision::Symmetric))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, field_of_view_by_radius);
+criterion_main!(benches);
@@ -0,0 +1,300 @@
+use crate::{Coord, Metric4, Position4, Position6, Position6Axial, Position6Cube};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+/// The tiles of a square grid area row by row, optionally without an inner area.
+/// Tiles outside the coordinate range are skipped.
+#[derive(Debug, Clone)]
+pub struct Area4<C: Coord = i16> {
+    x: i128,
+    y: i128,
+    metric: Metric4,
+    outer: u32,
+    inner: Option<u32>,
+    dy: i128,
+    dx: Option<i128>,
+    width: PhantomData<C>,
+}
+
+impl<C: Coord> Area4<C> {
+    fn new(center: &Position4<C>, metric: Metric4, outer: u32, inner: Option<u32>) -> Self {
+        Area4 {
+            x: center.x.widen(),
+            y: center.y.widen(),
+            metric,
+            outer,
+            inner,
+            dy: -i128::from(outer),
+            dx: None,
+            width: PhantomData,
+        }
+    }
+}
+
+impl<C: Coord> Iterator for Area4<C> {
+    type Item = Position4<C>;
+
+    fn next(&mut self) -> Option<Position4<C>> {
+        loop {
+            let row = self.dy.unsigned_abs() as u32;
+            let outer = self.metric.half_width(self.outer, row)?;
+            let dx = self.dx.unwrap_or(-outer);
+            if dx > outer {
+                self.dy += 1;
+                self.dx = None;
+                continue;
+            }
+            if let Some(inner) = self.inner.and_then(|i| self.metric.half_width(i, row)) {
+                if dx.abs() <= inner {
+                    self.dx = Some(inner + 1);
+                    continue;
+                }
+            }
+            self.dx = Some(dx + 1);
+            if let (Some(x), Some(y)) = (C::narrow(self.x + dx), C::narrow(self.y + self.dy)) {
+                return Some(Position4 { x, y });
+            }
+        }
+    }
+}
+impl<C: Coord> FusedIterator for Area4<C> {}
+
+/// The rings of a square grid area from the center outwards.
+#[derive(Debug, Clone)]
+pub struct Spiral4<C: Coord = i16> {
+    center: Position4<C>,
+    ring: Area4<C>,
+    radius: u32,
+}
+
+impl<C: Coord> Iterator for Spiral4<C> {
+    type Item = Position4<C>;
+
+    fn next(&mut self) -> Option<Position4<C>> {
+        loop {
+            if let Some(p) = self.ring.next() {
+                return Some(p);
+            }
+            if self.ring.outer >= self.radius {
+                return None;
+            }
+            self.ring = self.center.ring(self.ring.outer + 1, self.ring.metric);
+        }
+    }
+}
+impl<C: Coord> FusedIterator for Spiral4<C> {}
+
+impl<C: Coord> Position4<C> {
+    /// Tiles at most `radius` away.
+    pub fn within(&self, radius: u32, metric: Metric4) -> Area4<C> {
+        Area4::new(self, metric, radius, None)
+    }
+
+    /// Tiles exactly `radius` away, those `within(radius)` but not `within(radius - 1)`.
+    pub fn ring(&self, radius: u32, metric: Metric4) -> Area4<C> {
+        Area4::new(self, metric, radius, radius.checked_sub(1))
+    }
+
+    /// Tiles `within(radius)` ordered by ring, starting at the center.
+    pub fn spiral(&self, radius: u32, metric: Metric4) -> Spiral4<C> {
+        Spiral4 {
+            center: *self,
+            ring: self.ring(0, metric),
+            radius,
+        }
+    }
+}
+
+const HEX_DIRECTIONS: [(i128, i128, i128); 6] = [
+    (1, 0, -1),
+    (1, -1, 0),
+    (0, -1, 1),
+    (-1, 0, 1),
+    (-1, 1, 0),
+    (0, 1, -1),
+];
+
+pub(crate) fn to_hex<P: Position6>(x: i128, y: i128, z: i128) -> Option<P> {
+    Some(P::from_cube(Position6Cube {
+        x: Coord::narrow(x)?,
+        y: Coord::narrow(y)?,
+        z: Coord::narrow(z)?,
+    }))
+}
+
+pub(crate) fn cube_of(p: &impl Position6) -> (i128, i128, i128) {
+    let c = p.to_cube();
+    (c.x.widen(), c.y.widen(), c.z.widen())
+}
+
+/// Hexes at most `radius` away, column by column.
+#[derive(Debug, Clone)]
+pub struct HexArea<P> {
+    center: (i128, i128, i128),
+    radius: i128,
+    dx: i128,
+    dy: i128,
+    position: PhantomData<P>,
+}
+
+impl<P: Position6> HexArea<P> {
+    pub(crate) fn new(center: &P, radius: u32) -> Self {
+        let radius = i128::from(radius);
+        let mut area = HexArea {
+            center: cube_of(center),
+            radius,
+            dx: -radius,
+            dy: 0,
+            position: PhantomData,
+        };
+        area.start_column();
+        area
+    }
+
+    fn start_column(&mut self) {
+        self.dy = (-self.radius).max(-self.dx - self.radius);
+    }
+}
+
+impl<P: Position6> Iterator for HexArea<P> {
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        loop {
+            if self.dx > self.radius {
+                return None;
+            }
+            if self.dy > self.radius.min(-self.dx + self.radius) {
+                self.dx += 1;
+                self.start_column();
+                continue;
+            }
+            let (dx, dy) = (self.dx, self.dy);
+            self.dy += 1;
+            let (x, y, z) = self.center;
+            if let Some(p) = to_hex(x + dx, y + dy, z - dx - dy) {
+                return Some(p);
+            }
+        }
+    }
+}
+impl<P: Position6> FusedIterator for HexArea<P> {}
+
+/// Hexes exactly `radius` away, walking around the center.
+#[derive(Debug, Clone)]
+pub struct HexRing<P> {
+    current: (i128, i128, i128),
+    radius: u32,
+    side: usize,
+    step: u32,
+    position: PhantomData<P>,
+}
+
+impl<P: Position6> HexRing<P> {
+    fn new(center: (i128, i128, i128), radius: u32) -> Self {
+        let (dx, dy, dz) = HEX_DIRECTIONS[4];
+        let r = i128::from(radius);
+        HexRing {
+            current: (center.0 + dx * r, center.1 + dy * r, center.2 + dz * r),
+            radius,
+            side: 0,
+            step: 0,
+            position: PhantomData,
+        }
+    }
+}
+
+impl<P: Position6> Iterator for HexRing<P> {
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        loop {
+            if self.side >= 6 {
+                return None;
+            }
+            let (x, y, z) = self.current;
+            if self.radius == 0 {
+                self.side = 6;
+            } else {
+                let (dx, dy, dz) = HEX_DIRECTIONS[self.side];
+                self.current = (x + dx, y + dy, z + dz);
+                self.step += 1;
+                if self.step == self.radius {
+                    self.step = 0;
+                    self.side += 1;
+                }
+            }
+            if let Some(p) = to_hex(x, y, z) {
+                return Some(p);
+            }
+        }
+    }
+}
+impl<P: Position6> FusedIterator for HexRing<P> {}
+
+/// The rings of a hex area from the center outwards.
+#[derive(Debug, Clone)]
+pub struct HexSpiral<P> {
+    center: (i128, i128, i128),
+    ring: HexRing<P>,
+    radius: u32,
+}
+
+impl<P: Position6> Iterator for HexSpiral<P> {
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        loop {
+            if let Some(p) = self.ring.next() {
+                return Some(p);
+            }
+            if self.ring.radius >= self.radius {
+                return None;
+            }
+            self.ring = HexRing::new(self.center, self.ring.radius + 1);
+        }
+    }
+}
+impl<P: Position6> FusedIterator for HexSpiral<P> {}
+
+impl<C: Coord> Position6Axial<C> {
+    /// Hexes at most `radius` away.
+    pub fn within(&self, radius: u32) -> HexArea<Position6Axial<C>> {
+        HexArea::new(self, radius)
+    }
+
+    /// Hexes exactly `radius` away.
+    pub fn ring(&self, radius: u32) -> HexRing<Position6Axial<C>> {
+        HexRing::new(cube_of(self), radius)
+    }
+
+    /// Hexes `within(radius)` ordered by ring, starting at the center.
+    pub fn spiral(&self, radius: u32) -> HexSpiral<Position6Axial<C>> {
+        HexSpiral {
+            center: cube_of(self),
+            ring: HexRing::new(cube_of(self), 0),
+            radius,
+        }
+    }
+}
+
+impl<C: Coord> Position6Cube<C> {
+    /// Hexes at most `radius` away.
+    pub fn within(&self, radius: u32) -> HexArea<Position6Cube<C>> {
+        HexArea::new(self, radius)
+    }
+
+    /// Hexes exactly `radius` away.
+    pub fn ring(&self, radius: u32) -> HexRing<Position6Cube<C>> {
+        HexRing::new(cube_of(self), radius)
+    }
+
+    /// Hexes `within(radius)` ordered by ring, starting at the center.
+    pub fn spiral(&self, radius: u32) -> HexSpiral<Position6Cube<C>> {
+        HexSpiral {
+            center: cube_of(self),
+            ring: HexRing::new(cube_of(self), 0),
+            radius,
+        }
+    }
+}
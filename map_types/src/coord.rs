@@ -0,0 +1,105 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use structured_digest::{Decode, Digestable};
+
+/// The integer type of position coordinates. `i16` is the default, `i32` and `i64` fit world
+/// maps, `i8` small battle maps.
+///
+/// Geometry is computed in `i128`, so it works for any two positions of the same width.
+pub trait Coord:
+    Copy
+    + Eq
+    + Ord
+    + Hash
+    + Debug
+    + Default
+    + Add<Output = Self>
+    + AddAssign
+    + Sub<Output = Self>
+    + SubAssign
+    + Neg<Output = Self>
+    + Digestable
+    + Decode
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + 'static
+{
+    const BITS: u32;
+    /// The digest schema version of positions with this width. `i16` positions predate the
+    /// others and keep 0.
+    const POSITION_SCHEMA: u32;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn saturating_add(self, rhs: Self) -> Self;
+    fn saturating_sub(self, rhs: Self) -> Self;
+
+    fn widen(self) -> i128;
+    /// `None` outside the range of the type.
+    fn narrow(value: i128) -> Option<Self>;
+
+    fn to_f64(self) -> f64 {
+        self.widen() as f64
+    }
+
+    /// Rounds to the nearest integer, saturating at the ends of the range.
+    fn round_f64(value: f64) -> Self;
+}
+
+macro_rules! coord_impl {
+    ($($t:ident)*) => {$(
+        impl Coord for $t {
+            const BITS: u32 = $t::BITS;
+            const POSITION_SCHEMA: u32 = if $t::BITS == 16 { 0 } else { $t::BITS };
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                $t::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                $t::checked_sub(self, rhs)
+            }
+
+            fn checked_neg(self) -> Option<Self> {
+                $t::checked_neg(self)
+            }
+
+            fn saturating_add(self, rhs: Self) -> Self {
+                $t::saturating_add(self, rhs)
+            }
+
+            fn saturating_sub(self, rhs: Self) -> Self {
+                $t::saturating_sub(self, rhs)
+            }
+
+            fn widen(self) -> i128 {
+                self.into()
+            }
+
+            fn narrow(value: i128) -> Option<Self> {
+                $t::try_from(value).ok()
+            }
+
+            fn round_f64(value: f64) -> Self {
+                value.round() as $t
+            }
+        }
+    )*};
+}
+coord_impl!(i8 i16 i32 i64);
+
+/// A distance in tiles, `u32::MAX` if it is larger.
+pub(crate) fn saturate(value: i128) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
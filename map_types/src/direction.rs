@@ -0,0 +1,299 @@
+use crate::position::step;
+use crate::{Coord, Layered, Position4, Position6, Position6Axial, Position6Cube};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// The sides of a tile. Directions are named for a screen with y pointing down, so they turn
+/// clockwise in the order of `ALL`, which is also the order of `PositionHelper::neighbours`.
+///
+/// Orientations and border flags store a direction as its index, so it serializes as one.
+pub trait Direction: Copy + Eq + Hash + Debug + 'static {
+    /// Clockwise, starting east.
+    const ALL: &'static [Self];
+
+    fn index(self) -> usize;
+
+    /// `None` for indices past the last direction.
+    fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// The grid step towards the neighbour, in axial coordinates on hex grids.
+    fn offset(self) -> (i8, i8);
+
+    fn opposite(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() / 2) % Self::ALL.len()]
+    }
+
+    fn rotate_cw(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    fn rotate_ccw(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn iter() -> std::iter::Copied<std::slice::Iter<'static, Self>> {
+        Self::ALL.iter().copied()
+    }
+}
+
+/// The sides of a square.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum Direction4 {
+    East,
+    South,
+    West,
+    North,
+}
+
+/// The sides and corners of a square.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum Direction8 {
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+    North,
+    NorthEast,
+}
+
+/// The sides of a hex, named for pointy top layouts. On flat top layouts each lies a twelfth
+/// turn further clockwise, `East` points south east.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum Direction6 {
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthEast,
+}
+
+/// Directions across the edges of a tile rather than its corners, each half of them pointing
+/// away from the other half.
+pub trait Side: Direction {}
+
+impl Side for Direction4 {}
+
+impl Side for Direction6 {}
+
+/// An orientation index past the last direction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NoSuchDirection(pub u8);
+
+impl Display for NoSuchDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no direction with index {}", self.0)
+    }
+}
+
+impl Error for NoSuchDirection {}
+
+macro_rules! direction_impl {
+    ($name:ident, [$($variant:ident = $offset:expr),*]) => {
+        impl Direction for $name {
+            const ALL: &'static [Self] = &[$($name::$variant),*];
+
+            fn index(self) -> usize {
+                self as usize
+            }
+
+            fn offset(self) -> (i8, i8) {
+                match self {
+                    $($name::$variant => $offset),*
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(d: $name) -> u8 {
+                d as u8
+            }
+        }
+
+        impl TryFrom<u8> for $name {
+            type Error = NoSuchDirection;
+
+            fn try_from(index: u8) -> Result<Self, NoSuchDirection> {
+                $name::from_index(index.into()).ok_or(NoSuchDirection(index))
+            }
+        }
+    };
+}
+
+direction_impl!(
+    Direction4,
+    [
+        East = (1, 0),
+        South = (0, 1),
+        West = (-1, 0),
+        North = (0, -1)
+    ]
+);
+direction_impl!(
+    Direction8,
+    [
+        East = (1, 0),
+        SouthEast = (1, 1),
+        South = (0, 1),
+        SouthWest = (-1, 1),
+        West = (-1, 0),
+        NorthWest = (-1, -1),
+        North = (0, -1),
+        NorthEast = (1, -1)
+    ]
+);
+direction_impl!(
+    Direction6,
+    [
+        East = (1, 0),
+        SouthEast = (0, 1),
+        SouthWest = (-1, 1),
+        West = (-1, 0),
+        NorthWest = (0, -1),
+        NorthEast = (1, -1)
+    ]
+);
+
+/// Positions with a neighbour in each direction `D`.
+pub trait Adjacent<D: Direction>: Sized {
+    /// Panics or wraps like `+` at the edge of the coordinate range.
+    fn neighbour(&self, direction: D) -> Self;
+
+    /// `None` at the edge of the coordinate range.
+    fn checked_neighbour(&self, direction: D) -> Option<Self>;
+
+    /// The direction pointing closest to `other`, `None` for the position itself. Ties go to
+    /// the direction first in `D::ALL`.
+    fn direction_to(&self, other: &Self) -> Option<D>;
+}
+
+/// The direction whose offset has the largest cosine with `(dx, dy)` under `dot`.
+fn closest<D: Direction>(
+    dx: f64,
+    dy: f64,
+    dot: impl Fn((f64, f64), (f64, f64)) -> f64,
+) -> Option<D> {
+    if dx == 0.0 && dy == 0.0 {
+        return None;
+    }
+    let (mut best, mut best_cos) = (None, f64::NEG_INFINITY);
+    for d in D::iter() {
+        let (ox, oy) = d.offset();
+        let o = (f64::from(ox), f64::from(oy));
+        let cos = dot((dx, dy), o) / dot(o, o).sqrt();
+        if cos > best_cos {
+            best = Some(d);
+            best_cos = cos;
+        }
+    }
+    best
+}
+
+fn square_dot(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+// axial axes are a sixth of a turn (60°) apart
+fn hex_dot(a: (f64, f64), b: (f64, f64)) -> f64 {
+    a.0 * b.0 + a.1 * b.1 + (a.0 * b.1 + a.1 * b.0) / 2.0
+}
+
+fn delta<C: Coord>(a: C, b: C) -> f64 {
+    (b.widen() - a.widen()) as f64
+}
+
+macro_rules! square_adjacent {
+    ($dir:ident) => {
+        impl<C: Coord> Adjacent<$dir> for Position4<C> {
+            fn neighbour(&self, direction: $dir) -> Self {
+                let (x, y) = direction.offset();
+                Position4 {
+                    x: self.x + step(x),
+                    y: self.y + step(y),
+                }
+            }
+
+            fn checked_neighbour(&self, direction: $dir) -> Option<Self> {
+                let (x, y) = direction.offset();
+                Some(Position4 {
+                    x: self.x.checked_add(C::narrow(x.into())?)?,
+                    y: self.y.checked_add(C::narrow(y.into())?)?,
+                })
+            }
+
+            fn direction_to(&self, other: &Self) -> Option<$dir> {
+                closest(delta(self.x, other.x), delta(self.y, other.y), square_dot)
+            }
+        }
+    };
+}
+
+square_adjacent!(Direction4);
+square_adjacent!(Direction8);
+
+impl<C: Coord> Adjacent<Direction6> for Position6Axial<C> {
+    fn neighbour(&self, direction: Direction6) -> Self {
+        let (x, y) = direction.offset();
+        Position6Axial {
+            x: self.x + step(x),
+            y: self.y + step(y),
+        }
+    }
+
+    fn checked_neighbour(&self, direction: Direction6) -> Option<Self> {
+        let (x, y) = direction.offset();
+        Some(Position6Axial {
+            x: self.x.checked_add(C::narrow(x.into())?)?,
+            y: self.y.checked_add(C::narrow(y.into())?)?,
+        })
+    }
+
+    fn direction_to(&self, other: &Self) -> Option<Direction6> {
+        closest(delta(self.x, other.x), delta(self.y, other.y), hex_dot)
+    }
+}
+
+impl<C: Coord> Adjacent<Direction6> for Position6Cube<C> {
+    fn neighbour(&self, direction: Direction6) -> Self {
+        self.to_axial().neighbour(direction).to_cube()
+    }
+
+    fn checked_neighbour(&self, direction: Direction6) -> Option<Self> {
+        self.to_axial()
+            .checked_neighbour(direction)?
+            .checked_to_cube()
+    }
+
+    fn direction_to(&self, other: &Self) -> Option<Direction6> {
+        self.to_axial().direction_to(&other.to_axial())
+    }
+}
+
+/// Directions stay on the level.
+impl<D: Direction, P: Adjacent<D>> Adjacent<D> for Layered<P> {
+    fn neighbour(&self, direction: D) -> Self {
+        Layered::new(self.position.neighbour(direction), self.level)
+    }
+
+    fn checked_neighbour(&self, direction: D) -> Option<Self> {
+        Some(Layered::new(
+            self.position.checked_neighbour(direction)?,
+            self.level,
+        ))
+    }
+
+    /// `None` straight above or below.
+    fn direction_to(&self, other: &Self) -> Option<D> {
+        self.position.direction_to(&other.position)
+    }
+}
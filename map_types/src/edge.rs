@@ -0,0 +1,228 @@
+use crate::{Adjacent, Borders, Direction4, Direction6, Position4, Position6Axial, Side};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::Display;
+
+/// The edge between two neighbouring tiles, stored once so walls, doors, rivers and roads on
+/// it can't disagree. It is kept as a side of one of the tiles, the east or south side on square
+/// grids and the east, south east or south west side on hex grids.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(
+    try_from = "Raw<P, D>",
+    into = "Raw<P, D>",
+    bound(
+        serialize = "P: Serialize + Clone, D: Serialize + Clone",
+        deserialize = "P: Deserialize<'de> + Adjacent<D> + Clone, D: Deserialize<'de> + Side"
+    )
+)]
+pub struct Edge<P, D> {
+    tile: P,
+    side: D,
+}
+
+/// The corner where three hexes or four squares meet. It is kept as the corner of one of the
+/// tiles clockwise after one of its sides, after the east side on square grids and after the
+/// east or south east side on hex grids.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(
+    try_from = "Raw<P, D>",
+    into = "Raw<P, D>",
+    bound(
+        serialize = "P: Serialize + Clone, D: Serialize + Clone",
+        deserialize = "P: Deserialize<'de> + Adjacent<D> + Clone, D: Deserialize<'de> + Side"
+    )
+)]
+pub struct Vertex<P, D> {
+    tile: P,
+    side: D,
+}
+
+pub type Edge4<C = i16> = Edge<Position4<C>, Direction4>;
+pub type Edge6<C = i16> = Edge<Position6Axial<C>, Direction6>;
+pub type Vertex4<C = i16> = Vertex<Position4<C>, Direction4>;
+pub type Vertex6<C = i16> = Vertex<Position6Axial<C>, Direction6>;
+
+/// An edge or corner at the end of the coordinate range, where not all its tiles exist.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct OutsideRange;
+
+impl Display for OutsideRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tiles outside the coordinate range")
+    }
+}
+
+impl Error for OutsideRange {}
+
+#[derive(Serialize, Deserialize)]
+struct Raw<P, D> {
+    tile: P,
+    side: D,
+}
+
+fn turn<D: Side>(side: D, steps: usize) -> D {
+    D::ALL[(side.index() + steps) % D::ALL.len()]
+}
+
+impl<P: Adjacent<D> + Clone, D: Side> Edge<P, D> {
+    /// The edge on `side` of `tile`. Panics or wraps like `Adjacent::neighbour` at the end of the
+    /// coordinate range.
+    pub fn new(tile: P, side: D) -> Self {
+        if side.index() < D::ALL.len() / 2 {
+            Edge { tile, side }
+        } else {
+            Edge {
+                tile: tile.neighbour(side),
+                side: side.opposite(),
+            }
+        }
+    }
+
+    /// `None` if the tile across the edge is outside the coordinate range.
+    pub fn checked_new(tile: P, side: D) -> Option<Self> {
+        let across = tile.checked_neighbour(side)?;
+        if side.index() < D::ALL.len() / 2 {
+            Some(Edge { tile, side })
+        } else {
+            Some(Edge {
+                tile: across,
+                side: side.opposite(),
+            })
+        }
+    }
+
+    /// The edges of `tile`, in the order of `D::ALL`.
+    pub fn around(tile: &P) -> Vec<Self> {
+        D::iter()
+            .map(|side| Edge::new(tile.clone(), side))
+            .collect()
+    }
+
+    pub fn tile(&self) -> &P {
+        &self.tile
+    }
+
+    pub fn side(&self) -> D {
+        self.side
+    }
+
+    /// The two tiles the edge separates.
+    pub fn tiles(&self) -> (P, P) {
+        (self.tile.clone(), self.tile.neighbour(self.side))
+    }
+
+    /// The corners at both ends, counterclockwise one first as seen from `tile`.
+    pub fn ends(&self) -> (Vertex<P, D>, Vertex<P, D>) {
+        (
+            Vertex::new(self.tile.clone(), self.side.rotate_ccw()),
+            Vertex::new(self.tile.clone(), self.side),
+        )
+    }
+
+    /// The border flags of `tile`, walled on the edges `wall` picks.
+    pub fn borders<B: Borders<Direction = D>>(tile: &P, mut wall: impl FnMut(&Self) -> bool) -> B {
+        B::from_walls(|side| wall(&Edge::new(tile.clone(), side)))
+    }
+}
+
+/// The tiles and sides naming the corner clockwise after `side` of `tile`, clockwise around
+/// the corner. `None` if `neighbour` finds no tile.
+fn walk<P, D: Side>(
+    tile: P,
+    side: D,
+    neighbour: impl Fn(&P, D) -> Option<P>,
+) -> Option<Vec<(P, D)>> {
+    let step = D::ALL.len() / 2 - 1;
+    let mut walk = vec![(tile, side)];
+    for _ in 1..D::ALL.len() / step {
+        let (tile, side) = walk.last().expect("starts with the tile");
+        let next = (neighbour(tile, *side)?, turn(*side, step));
+        walk.push(next);
+    }
+    Some(walk)
+}
+
+impl<P: Adjacent<D> + Clone, D: Side> Vertex<P, D> {
+    fn from_walk(walk: Vec<(P, D)>) -> Self {
+        let step = D::ALL.len() / 2 - 1;
+        let (tile, side) = walk
+            .into_iter()
+            .find(|(_, side)| side.index() < step)
+            .expect("one of the sides names the corner");
+        Vertex { tile, side }
+    }
+
+    fn walk(&self) -> Vec<(P, D)> {
+        walk(self.tile.clone(), self.side, |tile, side| {
+            Some(tile.neighbour(side))
+        })
+        .expect("neighbours exist")
+    }
+
+    /// The corner of `tile` clockwise after `side`. Panics or wraps like `Adjacent::neighbour`
+    /// at the end of the coordinate range.
+    pub fn new(tile: P, side: D) -> Self {
+        Vertex::from_walk(Vertex { tile, side }.walk())
+    }
+
+    /// `None` if one of the tiles at the corner is outside the coordinate range.
+    pub fn checked_new(tile: P, side: D) -> Option<Self> {
+        Some(Vertex::from_walk(walk(tile, side, |tile, side| {
+            tile.checked_neighbour(side)
+        })?))
+    }
+
+    /// The corners of `tile`, clockwise starting after its east side.
+    pub fn around(tile: &P) -> Vec<Self> {
+        D::iter()
+            .map(|side| Vertex::new(tile.clone(), side))
+            .collect()
+    }
+
+    pub fn tile(&self) -> &P {
+        &self.tile
+    }
+
+    pub fn side(&self) -> D {
+        self.side
+    }
+
+    /// The tiles meeting at the corner, clockwise around it starting with `tile`.
+    pub fn tiles(&self) -> Vec<P> {
+        self.walk().into_iter().map(|(tile, _)| tile).collect()
+    }
+
+    /// The edges meeting at the corner, clockwise around it starting with the one on `side` of
+    /// `tile`.
+    pub fn edges(&self) -> Vec<Edge<P, D>> {
+        self.walk()
+            .into_iter()
+            .map(|(tile, side)| Edge::new(tile, side))
+            .collect()
+    }
+}
+
+macro_rules! raw {
+    ($name:ident) => {
+        impl<P: Adjacent<D> + Clone, D: Side> TryFrom<Raw<P, D>> for $name<P, D> {
+            type Error = OutsideRange;
+
+            fn try_from(raw: Raw<P, D>) -> Result<Self, OutsideRange> {
+                $name::checked_new(raw.tile, raw.side).ok_or(OutsideRange)
+            }
+        }
+
+        impl<P, D> From<$name<P, D>> for Raw<P, D> {
+            fn from(v: $name<P, D>) -> Self {
+                Raw {
+                    tile: v.tile,
+                    side: v.side,
+                }
+            }
+        }
+    };
+}
+
+raw!(Edge);
+raw!(Vertex);
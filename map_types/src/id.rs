@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use sha2::digest::consts::U32;
+use sha2::{Digest, Sha256, Sha512Trunc256};
+use std::convert::TryFrom;
+use structured_digest::{Decode, DecodeError, Digestable, Encoding, Reader, Sink, Version};
+
+/// The hash function a `ChangeId` was computed with.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512Trunc256,
+    Blake2s256,
+    Blake3,
+}
+impl HashAlgorithm {
+    /// The multicodec code, used in multihashes and in the encoding of a `ChangeId`.
+    pub fn code(self) -> u32 {
+        match self {
+            HashAlgorithm::Sha256 => 0x12,
+            HashAlgorithm::Sha512Trunc256 => 0x1015,
+            HashAlgorithm::Blake2s256 => 0xb260,
+            HashAlgorithm::Blake3 => 0x1e,
+        }
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0x12 => Some(HashAlgorithm::Sha256),
+            0x1015 => Some(HashAlgorithm::Sha512Trunc256),
+            0xb260 => Some(HashAlgorithm::Blake2s256),
+            0x1e => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+}
+
+/// A digest usable for change ids.
+pub trait IdDigest: Digest<OutputSize = U32> {
+    const ALGORITHM: HashAlgorithm;
+}
+impl IdDigest for Sha256 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Sha256;
+}
+impl IdDigest for Sha512Trunc256 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Sha512Trunc256;
+}
+impl IdDigest for blake2::Blake2s {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Blake2s256;
+}
+impl IdDigest for blake3::Hasher {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Blake3;
+}
+
+/// Identifies a change by its hash and the algorithm that produced it.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(from = "IdRepr")]
+pub struct ChangeId {
+    pub algorithm: HashAlgorithm,
+    pub hash: [u8; 32],
+}
+impl ChangeId {
+    pub fn new<D: IdDigest>(hash: sha2::digest::Output<D>) -> Self {
+        ChangeId {
+            algorithm: D::ALGORITHM,
+            hash: hash.into(),
+        }
+    }
+
+    /// Code, length and hash, each length as unsigned varint.
+    pub fn to_multihash(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(36);
+        write_varint(&mut bytes, self.algorithm.code().into());
+        write_varint(&mut bytes, self.hash.len() as u64);
+        bytes.extend_from_slice(&self.hash);
+        bytes
+    }
+
+    pub fn from_multihash(mut bytes: &[u8]) -> Option<Self> {
+        let code = u32::try_from(read_varint(&mut bytes)?).ok()?;
+        let algorithm = HashAlgorithm::from_code(code)?;
+        if read_varint(&mut bytes)? != 32 {
+            return None;
+        }
+        Some(ChangeId {
+            algorithm,
+            hash: <[u8; 32]>::try_from(bytes).ok()?,
+        })
+    }
+}
+
+/// Ids without an algorithm are SHA-256, the only one before ids recorded it.
+impl From<[u8; 32]> for ChangeId {
+    fn from(hash: [u8; 32]) -> Self {
+        ChangeId {
+            algorithm: HashAlgorithm::Sha256,
+            hash,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IdRepr {
+    Legacy([u8; 32]),
+    Tagged {
+        algorithm: HashAlgorithm,
+        hash: [u8; 32],
+    },
+}
+impl From<IdRepr> for ChangeId {
+    fn from(r: IdRepr) -> Self {
+        match r {
+            IdRepr::Legacy(hash) => ChangeId::from(hash),
+            IdRepr::Tagged { algorithm, hash } => ChangeId { algorithm, hash },
+        }
+    }
+}
+
+// Up to Version::V1 only SHA-256 existed and ids are written as the bare hash
+impl Digestable for ChangeId {
+    fn digest<S: Sink + ?Sized>(&self, sink: &mut S, encoding: Encoding) {
+        if encoding.version >= Version::V2 {
+            self.algorithm.code().digest(sink, encoding);
+        }
+        sink.write(&self.hash)
+    }
+}
+impl Decode for ChangeId {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let algorithm = if reader.encoding().version >= Version::V2 {
+            let code = u32::decode(reader)?;
+            HashAlgorithm::from_code(code).ok_or(DecodeError::InvalidDiscriminant(code))?
+        } else {
+            HashAlgorithm::Sha256
+        };
+        Ok(ChangeId {
+            algorithm,
+            hash: <[u8; 32]>::decode(reader)?,
+        })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        bytes.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    bytes.push(v as u8)
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut v = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&b, rest) = bytes.split_first()?;
+        *bytes = rest;
+        v |= u64::from(b & 0x7f).checked_shl(shift)?;
+        if b & 0x80 == 0 {
+            return Some(v);
+        }
+    }
+    None
+}
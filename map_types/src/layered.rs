@@ -0,0 +1,213 @@
+use crate::coord::saturate;
+use crate::{Metric, Position, PositionHelper};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use structured_digest::{Decode, Digestable};
+
+/// A position on one level of a map with several floors. Higher levels are further up.
+///
+/// `PositionHelper::neighbours` stays on the level, moving between levels needs the stairs,
+/// ladders and pits of a `VerticalLinks`. Distances count a level change as one step.
+#[derive(
+    Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash, Default, Digestable, Decode,
+)]
+#[digest(tag = "map_types::Layered")]
+pub struct Layered<P> {
+    pub position: P,
+    pub level: i16,
+}
+
+impl<P> Layered<P> {
+    pub fn new(position: P, level: i16) -> Self {
+        Layered { position, level }
+    }
+}
+
+impl<P: PositionHelper> PositionHelper for Layered<P> {
+    fn neighbours(&self) -> Vec<Self> {
+        let level = self.level;
+        self.position
+            .neighbours()
+            .into_iter()
+            .map(|position| Layered { position, level })
+            .collect()
+    }
+
+    fn checked_neighbours(&self) -> Vec<Self> {
+        let level = self.level;
+        self.position
+            .checked_neighbours()
+            .into_iter()
+            .map(|position| Layered { position, level })
+            .collect()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(Layered {
+            position: self.position.checked_add(&rhs.position)?,
+            level: self.level.checked_add(rhs.level)?,
+        })
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Some(Layered {
+            position: self.position.checked_sub(&rhs.position)?,
+            level: self.level.checked_sub(rhs.level)?,
+        })
+    }
+
+    fn checked_neg(&self) -> Option<Self> {
+        Some(Layered {
+            position: self.position.checked_neg()?,
+            level: self.level.checked_neg()?,
+        })
+    }
+}
+
+impl<P> Position<Layered<P>> for Layered<P>
+where
+    P: Position<P> + Add<Output = P> + Sub<Output = P> + Neg<Output = P> + AddAssign + SubAssign,
+{
+    fn field_length(&self, other: &Layered<P>) -> u32 {
+        let climb = (i128::from(self.level) - i128::from(other.level)).abs();
+        saturate(i128::from(self.position.field_length(&other.position)) + climb)
+    }
+
+    /// Levels are as high as tiles are wide.
+    fn line_length(&self, other: &Layered<P>) -> f64 {
+        let planar = self.position.line_length(&other.position);
+        let climb = f64::from(self.level) - f64::from(other.level);
+        (planar * planar + climb * climb).sqrt()
+    }
+}
+
+impl<P: Add<Output = P>> Add for Layered<P> {
+    type Output = Layered<P>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Layered {
+            position: self.position + rhs.position,
+            level: self.level + rhs.level,
+        }
+    }
+}
+
+impl<P: AddAssign> AddAssign for Layered<P> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.position += rhs.position;
+        self.level += rhs.level;
+    }
+}
+
+impl<P: Sub<Output = P>> Sub for Layered<P> {
+    type Output = Layered<P>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Layered {
+            position: self.position - rhs.position,
+            level: self.level - rhs.level,
+        }
+    }
+}
+
+impl<P: SubAssign> SubAssign for Layered<P> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.position -= rhs.position;
+        self.level -= rhs.level;
+    }
+}
+
+impl<P: Neg<Output = P>> Neg for Layered<P> {
+    type Output = Layered<P>;
+
+    fn neg(self) -> Self::Output {
+        Layered {
+            position: -self.position,
+            level: -self.level,
+        }
+    }
+}
+
+impl<P> From<(P, i16)> for Layered<P> {
+    fn from(f: (P, i16)) -> Self {
+        Layered::new(f.0, f.1)
+    }
+}
+
+/// Stairs, ladders and pits, each leading from a tile straight up or down to the same tile on
+/// another level. As a `Metric` it adds them to the neighbours on the level, so pathfinding can
+/// change levels. Taking one costs `climb_cost` steps.
+#[derive(Debug, Clone)]
+pub struct VerticalLinks<P: Eq + Hash> {
+    pub climb_cost: u32,
+    links: HashMap<Layered<P>, Vec<i16>>,
+}
+
+impl<P: Eq + Hash + Clone> VerticalLinks<P> {
+    pub fn new(climb_cost: u32) -> Self {
+        VerticalLinks {
+            climb_cost,
+            links: HashMap::new(),
+        }
+    }
+
+    /// A way down from `from` to `to`, like a pit or a chute.
+    pub fn link_one_way(&mut self, position: P, from: i16, to: i16) {
+        let levels = self.links.entry(Layered::new(position, from)).or_default();
+        if !levels.contains(&to) {
+            levels.push(to);
+        }
+    }
+
+    /// A way in both directions, like stairs or a ladder.
+    pub fn link(&mut self, position: P, a: i16, b: i16) {
+        self.link_one_way(position.clone(), a, b);
+        self.link_one_way(position, b, a);
+    }
+
+    /// The levels reachable from a tile in one climb.
+    pub fn levels_from(&self, tile: &Layered<P>) -> &[i16] {
+        self.links.get(tile).map_or(&[], |levels| levels.as_slice())
+    }
+}
+
+impl<P> Metric<Layered<P>> for VerticalLinks<P>
+where
+    P: Position<P> + Eq + Hash + Clone,
+{
+    fn distance(&self, a: &Layered<P>, b: &Layered<P>) -> u32 {
+        let climb = (i128::from(a.level) - i128::from(b.level)).abs();
+        saturate(i128::from(a.position.field_length(&b.position)) + climb)
+    }
+
+    fn neighbours(&self, p: &Layered<P>) -> Vec<Layered<P>> {
+        let mut neighbours = p.checked_neighbours();
+        neighbours.extend(
+            self.levels_from(p)
+                .iter()
+                .map(|&level| Layered::new(p.position.clone(), level)),
+        );
+        neighbours
+    }
+
+    fn step_cost(&self, from: &Layered<P>, to: &Layered<P>) -> u32 {
+        if from.level == to.level {
+            1
+        } else {
+            self.climb_cost
+        }
+    }
+
+    /// Links lead straight up or down, so every planar step still has to be taken, plus at
+    /// least one climb to reach another level.
+    fn cost_estimate(&self, a: &Layered<P>, b: &Layered<P>) -> u32 {
+        let planar = a.position.field_length(&b.position);
+        if a.level == b.level {
+            planar
+        } else {
+            planar.saturating_add(self.climb_cost)
+        }
+    }
+}
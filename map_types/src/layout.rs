@@ -0,0 +1,150 @@
+use crate::{Coord, Layered, Position4, Position6, Position6Axial, Position6Cube};
+use nalgebra::{Matrix2, Point2, Vector2};
+use std::f64::consts::PI;
+
+/// How tiles sit on the screen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Orientation {
+    /// Row major matrix from grid coordinates to pixels, in units of `Layout::size`.
+    pub forward: [f64; 4],
+    pub corners: u8,
+    /// Angle of the first corner in fractions of a full turn.
+    pub start_angle: f64,
+}
+
+impl Orientation {
+    pub const SQUARE: Orientation = Orientation {
+        forward: [2.0, 0.0, 0.0, 2.0],
+        corners: 4,
+        start_angle: 0.125,
+    };
+    pub const POINTY_TOP: Orientation = Orientation {
+        forward: [1.732_050_807_568_877_2, 0.866_025_403_784_438_6, 0.0, 1.5],
+        corners: 6,
+        start_angle: 1.0 / 12.0,
+    };
+    pub const FLAT_TOP: Orientation = Orientation {
+        forward: [1.5, 0.0, 0.866_025_403_784_438_6, 1.732_050_807_568_877_2],
+        corners: 6,
+        start_angle: 0.0,
+    };
+
+    fn matrix(&self) -> Matrix2<f64> {
+        let f = self.forward;
+        Matrix2::new(f[0], f[1], f[2], f[3])
+    }
+}
+
+/// Positions that can be placed on a `Layout`.
+pub trait Planar: Sized {
+    /// The coordinates along the grid axes.
+    fn to_plane(&self) -> Vector2<f64>;
+    /// The tile containing the point with the given coordinates along the grid axes.
+    fn from_plane(v: Vector2<f64>) -> Self;
+}
+
+impl<C: Coord> Planar for Position4<C> {
+    fn to_plane(&self) -> Vector2<f64> {
+        Vector2::new(self.x.to_f64(), self.y.to_f64())
+    }
+
+    fn from_plane(v: Vector2<f64>) -> Self {
+        Position4 {
+            x: C::round_f64(v.x),
+            y: C::round_f64(v.y),
+        }
+    }
+}
+
+impl<C: Coord> Planar for Position6Cube<C> {
+    fn to_plane(&self) -> Vector2<f64> {
+        Vector2::new(self.x.to_f64(), self.y.to_f64())
+    }
+
+    fn from_plane(v: Vector2<f64>) -> Self {
+        Position6Cube::round(v.x, v.y, -v.x - v.y)
+    }
+}
+
+impl<C: Coord> Planar for Position6Axial<C> {
+    fn to_plane(&self) -> Vector2<f64> {
+        Vector2::new(self.x.to_f64(), self.y.to_f64())
+    }
+
+    fn from_plane(v: Vector2<f64>) -> Self {
+        Position6Cube::from_plane(v).to_axial()
+    }
+}
+
+/// Levels sit on top of each other, so a point on the screen has no level of its own and
+/// `from_plane` picks level 0.
+impl<P: Planar> Planar for Layered<P> {
+    fn to_plane(&self) -> Vector2<f64> {
+        self.position.to_plane()
+    }
+
+    fn from_plane(v: Vector2<f64>) -> Self {
+        Layered::new(P::from_plane(v), 0)
+    }
+}
+
+/// Maps tiles to pixels and back. `size` is the distance from the center of a hex to its
+/// corners, or half the side of a square, `origin` the pixel of the center of tile zero.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Layout {
+    pub orientation: Orientation,
+    pub size: Vector2<f64>,
+    pub origin: Point2<f64>,
+}
+
+impl Layout {
+    pub fn new(orientation: Orientation, size: Vector2<f64>, origin: Point2<f64>) -> Self {
+        Layout {
+            orientation,
+            size,
+            origin,
+        }
+    }
+
+    pub fn square(size: f64, origin: Point2<f64>) -> Self {
+        Layout::new(Orientation::SQUARE, Vector2::new(size, size), origin)
+    }
+
+    pub fn pointy_top(size: f64, origin: Point2<f64>) -> Self {
+        Layout::new(Orientation::POINTY_TOP, Vector2::new(size, size), origin)
+    }
+
+    pub fn flat_top(size: f64, origin: Point2<f64>) -> Self {
+        Layout::new(Orientation::FLAT_TOP, Vector2::new(size, size), origin)
+    }
+
+    /// The center of a tile.
+    pub fn to_pixel<P: Planar>(&self, p: &P) -> Point2<f64> {
+        self.origin + (self.orientation.matrix() * p.to_plane()).component_mul(&self.size)
+    }
+
+    /// The tile containing a pixel.
+    pub fn from_pixel<P: Planar>(&self, pixel: &Point2<f64>) -> P {
+        let scaled = (pixel - self.origin).component_div(&self.size);
+        let inverse = self
+            .orientation
+            .matrix()
+            .try_inverse()
+            .expect("orientation is invertible");
+        P::from_plane(inverse * scaled)
+    }
+
+    /// The corners of a tile, clockwise on screen.
+    pub fn corners<P: Planar>(&self, p: &P) -> Vec<Point2<f64>> {
+        let center = self.to_pixel(p);
+        let n = self.orientation.corners;
+        // squares reach their corners along the diagonal
+        let radius = if n == 4 { 2f64.sqrt() } else { 1.0 };
+        (0..n)
+            .map(|i| {
+                let angle = 2.0 * PI * (self.orientation.start_angle + f64::from(i) / f64::from(n));
+                center + Vector2::new(angle.cos(), angle.sin()).component_mul(&self.size) * radius
+            })
+            .collect()
+    }
+}
@@ -1,9 +1,66 @@
 use serde::{Deserialize, Serialize};
+use std::ops::{Index, IndexMut};
+
+pub mod coord;
+
+pub use coord::*;
 
 pub mod position;
 
 pub use position::*;
 
+pub mod offset;
+
+pub use offset::*;
+
+pub mod direction;
+
+pub use direction::*;
+
+pub mod edge;
+
+pub use edge::*;
+
+pub mod layered;
+
+pub use layered::*;
+
+pub mod transform;
+
+pub use transform::*;
+
+pub mod line;
+
+pub use line::*;
+
+pub mod metric;
+
+pub use metric::*;
+
+pub mod area;
+
+pub use area::*;
+
+pub mod region;
+
+pub use region::*;
+
+pub mod pathfinding;
+
+pub use pathfinding::*;
+
+pub mod visibility;
+
+pub use visibility::*;
+
+pub mod layout;
+
+pub use layout::*;
+
+pub mod id;
+
+pub use id::*;
+
 pub mod transport;
 
 pub use transport::*;
@@ -12,15 +69,99 @@ pub mod storage;
 
 pub use storage::*;
 
-pub trait Borders {}
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
-struct Borders4(bool, bool, bool, bool);
-impl Borders for Borders4 {}
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
-struct Borders6(bool, bool, bool, bool, bool, bool);
-impl Borders for Borders6 {}
+/// Walls on the sides of a tile. `Edge::borders` derives them from walls stored per edge.
+pub trait Borders {
+    type Direction: Side;
+
+    fn is_wall(&self, side: Self::Direction) -> bool;
+    /// Walled on the sides `wall` picks.
+    fn from_walls(wall: impl FnMut(Self::Direction) -> bool) -> Self;
+}
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Borders4(pub bool, pub bool, pub bool, pub bool);
+impl Borders for Borders4 {
+    type Direction = Direction4;
+
+    fn is_wall(&self, side: Direction4) -> bool {
+        self[side]
+    }
+
+    fn from_walls(mut wall: impl FnMut(Direction4) -> bool) -> Self {
+        let mut borders = Borders4::default();
+        for side in Direction4::iter() {
+            borders[side] = wall(side);
+        }
+        borders
+    }
+}
+impl Index<Direction4> for Borders4 {
+    type Output = bool;
+
+    fn index(&self, side: Direction4) -> &bool {
+        match side {
+            Direction4::East => &self.0,
+            Direction4::South => &self.1,
+            Direction4::West => &self.2,
+            Direction4::North => &self.3,
+        }
+    }
+}
+impl IndexMut<Direction4> for Borders4 {
+    fn index_mut(&mut self, side: Direction4) -> &mut bool {
+        match side {
+            Direction4::East => &mut self.0,
+            Direction4::South => &mut self.1,
+            Direction4::West => &mut self.2,
+            Direction4::North => &mut self.3,
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Borders6(pub bool, pub bool, pub bool, pub bool, pub bool, pub bool);
+impl Borders for Borders6 {
+    type Direction = Direction6;
+
+    fn is_wall(&self, side: Direction6) -> bool {
+        self[side]
+    }
+
+    fn from_walls(mut wall: impl FnMut(Direction6) -> bool) -> Self {
+        let mut borders = Borders6::default();
+        for side in Direction6::iter() {
+            borders[side] = wall(side);
+        }
+        borders
+    }
+}
+impl Index<Direction6> for Borders6 {
+    type Output = bool;
+
+    fn index(&self, side: Direction6) -> &bool {
+        match side {
+            Direction6::East => &self.0,
+            Direction6::SouthEast => &self.1,
+            Direction6::SouthWest => &self.2,
+            Direction6::West => &self.3,
+            Direction6::NorthWest => &self.4,
+            Direction6::NorthEast => &self.5,
+        }
+    }
+}
+impl IndexMut<Direction6> for Borders6 {
+    fn index_mut(&mut self, side: Direction6) -> &mut bool {
+        match side {
+            Direction6::East => &mut self.0,
+            Direction6::SouthEast => &mut self.1,
+            Direction6::SouthWest => &mut self.2,
+            Direction6::West => &mut self.3,
+            Direction6::NorthWest => &mut self.4,
+            Direction6::NorthEast => &mut self.5,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[allow(dead_code)]
 struct Element<P: Position<P>> {
     id:u64,
     variant: u16,
@@ -28,21 +169,24 @@ struct Element<P: Position<P>> {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(dead_code)]
 struct Entity<P: Position<P>> {
     id: u64,
     ent_type: String,
     variant: u8,
+    /// `Direction::index` of the facing on the grid of `P`.
     orientation: u8,
     base_position: P,
     elements: Option<Vec<Element<P>>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(dead_code)]
 struct RenderEntity<P: Position<P>, B: Borders> {
     id: u64,
     ent_type: String,
     variant: u8,
-    orientation: u8,
+    orientation: B::Direction,
     base_position: P,
     elements: Option<Vec<P>>,
     impassable_tiles: Vec<Vec<bool>>,
@@ -0,0 +1,248 @@
+use crate::{Coord, Layered, Position4, Position6, Position6Axial, Position6Cube};
+use std::convert::TryFrom;
+use std::iter::{FusedIterator, Map};
+use std::marker::PhantomData;
+
+/// Tiles on the straight line between two positions, both ends included.
+pub trait Line: Sized {
+    type Iter: Iterator<Item = Self>;
+
+    fn line_to(&self, other: &Self) -> Self::Iter;
+}
+
+/// Bresenham's line on a square grid, one tile per step along the longer axis.
+#[derive(Debug, Clone)]
+pub struct Bresenham<C: Coord = i16> {
+    x: i128,
+    y: i128,
+    dx: i128,
+    dy: i128,
+    sx: i128,
+    sy: i128,
+    err: i128,
+    remaining: u128,
+    width: PhantomData<C>,
+}
+
+impl<C: Coord> Iterator for Bresenham<C> {
+    type Item = Position4<C>;
+
+    fn next(&mut self) -> Option<Position4<C>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let p = to_position4(self.x, self.y);
+        let e2 = 2 * self.err;
+        if e2 >= self.dy {
+            self.err += self.dy;
+            self.x += self.sx;
+        }
+        if e2 <= self.dx {
+            self.err += self.dx;
+            self.y += self.sy;
+        }
+        Some(p)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        (len, Some(len))
+    }
+}
+impl<C: Coord> ExactSizeIterator for Bresenham<C> {}
+impl<C: Coord> FusedIterator for Bresenham<C> {}
+
+impl<C: Coord> Line for Position4<C> {
+    type Iter = Bresenham<C>;
+
+    fn line_to(&self, other: &Position4<C>) -> Bresenham<C> {
+        let dx = (other.x.widen() - self.x.widen()).abs();
+        let dy = -(other.y.widen() - self.y.widen()).abs();
+        Bresenham {
+            x: self.x.widen(),
+            y: self.y.widen(),
+            dx,
+            dy,
+            sx: if other.x < self.x { -1 } else { 1 },
+            sy: if other.y < self.y { -1 } else { 1 },
+            err: dx + dy,
+            remaining: dx.max(-dy) as u128 + 1,
+            width: PhantomData,
+        }
+    }
+}
+
+/// Every tile the line between the centers of two tiles passes through. Consecutive tiles share
+/// an edge, except where the line passes exactly through a corner and steps diagonally.
+#[derive(Debug, Clone)]
+pub struct Supercover<C: Coord = i16> {
+    x: i128,
+    y: i128,
+    ix: i128,
+    iy: i128,
+    nx: i128,
+    ny: i128,
+    sx: i128,
+    sy: i128,
+    done: bool,
+    width: PhantomData<C>,
+}
+
+impl<C: Coord> Iterator for Supercover<C> {
+    type Item = Position4<C>;
+
+    fn next(&mut self) -> Option<Position4<C>> {
+        if self.done {
+            return None;
+        }
+        let p = to_position4(self.x, self.y);
+        if self.ix >= self.nx && self.iy >= self.ny {
+            self.done = true;
+            return Some(p);
+        }
+        let decision = (1 + 2 * self.ix) * self.ny - (1 + 2 * self.iy) * self.nx;
+        if decision <= 0 {
+            self.x += self.sx;
+            self.ix += 1;
+        }
+        if decision >= 0 {
+            self.y += self.sy;
+            self.iy += 1;
+        }
+        Some(p)
+    }
+}
+impl<C: Coord> FusedIterator for Supercover<C> {}
+
+impl<C: Coord> Position4<C> {
+    pub fn supercover_to(&self, other: &Position4<C>) -> Supercover<C> {
+        let dx = other.x.widen() - self.x.widen();
+        let dy = other.y.widen() - self.y.widen();
+        Supercover {
+            x: self.x.widen(),
+            y: self.y.widen(),
+            ix: 0,
+            iy: 0,
+            nx: dx.abs(),
+            ny: dy.abs(),
+            sx: if dx < 0 { -1 } else { 1 },
+            sy: if dy < 0 { -1 } else { 1 },
+            done: false,
+            width: PhantomData,
+        }
+    }
+}
+
+// the walks never leave the box spanned by both ends
+fn to_position4<C: Coord>(x: i128, y: i128) -> Position4<C> {
+    Position4 {
+        x: C::narrow(x).expect("between both ends"),
+        y: C::narrow(y).expect("between both ends"),
+    }
+}
+
+/// Hexes on the line between two hex centers, found by rounding evenly spaced points in cube
+/// coordinates. The ends are nudged slightly so points on an edge always round the same way.
+#[derive(Debug, Clone)]
+pub struct HexLine<C: Coord = i16> {
+    from: [f64; 3],
+    to: [f64; 3],
+    steps: u128,
+    next: u128,
+    width: PhantomData<C>,
+}
+
+impl<C: Coord> HexLine<C> {
+    fn new(from: &Position6Cube<C>, to: &Position6Cube<C>) -> Self {
+        let nudge = |p: &Position6Cube<C>| {
+            [
+                p.x.to_f64() + 1e-6,
+                p.y.to_f64() + 2e-6,
+                p.z.to_f64() - 3e-6,
+            ]
+        };
+        // not field_length, which saturates at u32::MAX
+        let d = |a: C, b: C| (a.widen() - b.widen()).unsigned_abs();
+        HexLine {
+            from: nudge(from),
+            to: nudge(to),
+            steps: (d(from.x, to.x) + d(from.y, to.y) + d(from.z, to.z)) / 2,
+            next: 0,
+            width: PhantomData,
+        }
+    }
+}
+
+impl<C: Coord> Iterator for HexLine<C> {
+    type Item = Position6Cube<C>;
+
+    fn next(&mut self) -> Option<Position6Cube<C>> {
+        if self.next > self.steps {
+            return None;
+        }
+        let t = if self.steps == 0 {
+            0.0
+        } else {
+            self.next as f64 / self.steps as f64
+        };
+        self.next += 1;
+        let lerp = |i: usize| self.from[i] + (self.to[i] - self.from[i]) * t;
+        Some(Position6Cube::round(lerp(0), lerp(1), lerp(2)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self
+            .steps
+            .checked_add(1)
+            .map(|end| end - self.next.min(end))
+            .and_then(|len| usize::try_from(len).ok())
+            .unwrap_or(usize::MAX);
+        (len, Some(len))
+    }
+}
+impl<C: Coord> ExactSizeIterator for HexLine<C> {}
+impl<C: Coord> FusedIterator for HexLine<C> {}
+
+impl<C: Coord> Line for Position6Cube<C> {
+    type Iter = HexLine<C>;
+
+    fn line_to(&self, other: &Position6Cube<C>) -> HexLine<C> {
+        HexLine::new(self, other)
+    }
+}
+
+impl<C: Coord> Line for Position6Axial<C> {
+    type Iter = Map<HexLine<C>, fn(Position6Cube<C>) -> Position6Axial<C>>;
+
+    fn line_to(&self, other: &Position6Axial<C>) -> Self::Iter {
+        HexLine::new(&self.to_cube(), &other.to_cube()).map(|c| c.to_axial())
+    }
+}
+
+/// Lines on one level stay on it. Lines between levels climb evenly along the way, one level or
+/// one tile per step, so straight up is a column of tiles.
+impl<P: Line + Clone> Line for Layered<P> {
+    type Iter = std::vec::IntoIter<Layered<P>>;
+
+    fn line_to(&self, other: &Layered<P>) -> Self::Iter {
+        let tiles: Vec<P> = self.position.line_to(&other.position).collect();
+        let climb = i32::from(other.level) - i32::from(self.level);
+        let steps = (tiles.len() - 1).max(climb.unsigned_abs() as usize);
+        let at = |k: usize, span: f64| {
+            if steps == 0 {
+                0.0
+            } else {
+                (span * k as f64 / steps as f64).round()
+            }
+        };
+        (0..=steps)
+            .map(|k| {
+                let position = tiles[at(k, (tiles.len() - 1) as f64) as usize].clone();
+                let level = i32::from(self.level) + at(k, f64::from(climb)) as i32;
+                Layered::new(position, level as i16)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
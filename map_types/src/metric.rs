@@ -0,0 +1,143 @@
+use crate::coord::saturate;
+use crate::{Adjacent, Coord, Direction, Direction4, Direction8, Position, Position4};
+
+/// A distance rule together with the moves it allows.
+///
+/// Pathfinding prices a step with `step_cost` and estimates with `cost_estimate`, both in the
+/// metric's own units, so rules with fractional steps can still be searched exactly.
+pub trait Metric<P> {
+    /// The distance in tiles as the rule counts it.
+    fn distance(&self, a: &P, b: &P) -> u32;
+
+    /// The tiles one move away, skipping those outside the coordinate range.
+    fn neighbours(&self, p: &P) -> Vec<P>;
+
+    /// The cost of a move between neighbours.
+    fn step_cost(&self, _from: &P, _to: &P) -> u32 {
+        1
+    }
+
+    /// A lower bound for the summed `step_cost` of moving from `a` to `b`.
+    fn cost_estimate(&self, a: &P, b: &P) -> u32 {
+        self.distance(a, b)
+    }
+}
+
+/// The metric every `Position` brings: `field_length` and `PositionHelper::neighbours`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Natural;
+
+impl<P: Position<P>> Metric<P> for Natural {
+    fn distance(&self, a: &P, b: &P) -> u32 {
+        a.field_length(b)
+    }
+
+    fn neighbours(&self, p: &P) -> Vec<P> {
+        p.checked_neighbours()
+    }
+}
+
+/// Distance rules for square grids.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Metric4 {
+    /// Orthogonal moves only, areas are diamonds.
+    Manhattan,
+    /// Diagonal moves count as one, areas are squares.
+    Chebyshev,
+    /// Every second diagonal move counts as two, the 5-10-5 rule. Pathfinding prices
+    /// diagonal moves at 1.5 since the rule depends on the order of the moves.
+    Alternating,
+    /// The distance between tile centers, rounded. Diagonal moves cost about 1.4.
+    Euclidean,
+}
+
+/// Absolute coordinate differences, the larger one first.
+fn deltas<C: Coord>(a: &Position4<C>, b: &Position4<C>) -> (u32, u32) {
+    let dx = saturate((a.x.widen() - b.x.widen()).abs());
+    let dy = saturate((a.y.widen() - b.y.widen()).abs());
+    (dx.max(dy), dx.min(dy))
+}
+
+fn isqrt(n: u128) -> u128 {
+    let mut r = (n as f64).sqrt() as u128;
+    while r * r > n {
+        r -= 1;
+    }
+    while (r + 1) * (r + 1) <= n {
+        r += 1;
+    }
+    r
+}
+
+impl Metric4 {
+    /// Half the width of the row `dy` away from the center of an area of `radius`.
+    pub(crate) fn half_width(self, radius: u32, dy: u32) -> Option<i128> {
+        if dy > radius {
+            return None;
+        }
+        let (radius, dy) = (u128::from(radius), u128::from(dy));
+        Some(match self {
+            Metric4::Manhattan => radius - dy,
+            Metric4::Chebyshev => radius,
+            Metric4::Alternating => {
+                // the row is the longer axis up to its diagonal, the shorter one beyond
+                if radius - dy / 2 >= dy {
+                    radius - dy / 2
+                } else {
+                    (dy - 1).min(2 * (radius - dy) + 1)
+                }
+            }
+            // round(sqrt(d)) <= r  <=>  d <= r * r + r
+            Metric4::Euclidean => isqrt(radius * radius + radius - dy * dy),
+        } as i128)
+    }
+}
+
+impl<C: Coord> Metric<Position4<C>> for Metric4 {
+    fn distance(&self, a: &Position4<C>, b: &Position4<C>) -> u32 {
+        let (long, short) = deltas(a, b);
+        match self {
+            Metric4::Manhattan => long.saturating_add(short),
+            Metric4::Chebyshev => long,
+            Metric4::Alternating => long.saturating_add(short / 2),
+            Metric4::Euclidean => {
+                let d = u128::from(long) * u128::from(long) + u128::from(short) * u128::from(short);
+                let r = isqrt(d);
+                // round half up: sqrt(d) >= r + 0.5  <=>  d > r * r + r
+                saturate((if d > r * r + r { r + 1 } else { r }) as i128)
+            }
+        }
+    }
+
+    fn neighbours(&self, p: &Position4<C>) -> Vec<Position4<C>> {
+        match self {
+            Metric4::Manhattan => Direction4::iter()
+                .filter_map(|d| p.checked_neighbour(d))
+                .collect(),
+            _ => Direction8::iter()
+                .filter_map(|d| p.checked_neighbour(d))
+                .collect(),
+        }
+    }
+
+    /// Alternating prices orthogonal moves at 2 and diagonal ones at 3, Euclidean at 5 and 7.
+    fn step_cost(&self, from: &Position4<C>, to: &Position4<C>) -> u32 {
+        let diagonal = from.x != to.x && from.y != to.y;
+        match (self, diagonal) {
+            (Metric4::Alternating, false) => 2,
+            (Metric4::Alternating, true) => 3,
+            (Metric4::Euclidean, false) => 5,
+            (Metric4::Euclidean, true) => 7,
+            _ => 1,
+        }
+    }
+
+    fn cost_estimate(&self, a: &Position4<C>, b: &Position4<C>) -> u32 {
+        let (long, short) = deltas(a, b);
+        match self {
+            Metric4::Alternating => saturate(2 * i128::from(long) + i128::from(short)),
+            Metric4::Euclidean => saturate(5 * i128::from(long - short) + 7 * i128::from(short)),
+            _ => self.distance(a, b),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{Coord, Position6Axial};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Which rows or columns of an offset grid are shoved by half a hex.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OffsetKind {
+    /// Pointy top hexes, odd rows shoved right.
+    OddR,
+    /// Pointy top hexes, even rows shoved right.
+    EvenR,
+    /// Flat top hexes, odd columns shoved down.
+    OddQ,
+    /// Flat top hexes, even columns shoved down.
+    EvenQ,
+}
+
+/// Which axis of a doubled grid steps by two.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DoubledKind {
+    /// Pointy top hexes, columns step by two and `col + row` is even.
+    Width,
+    /// Flat top hexes, rows step by two and `col + row` is even.
+    Height,
+}
+
+/// Row and column of a hex in a rectangular offset layout, as most map files store them.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(bound = "")]
+pub struct Position6Offset<C: Coord = i16> {
+    pub kind: OffsetKind,
+    pub col: C,
+    pub row: C,
+}
+
+/// Doubled coordinates, like offset ones rectangular but without parity cases in the math.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(bound = "")]
+pub struct Position6Doubled<C: Coord = i16> {
+    pub kind: DoubledKind,
+    pub col: C,
+    pub row: C,
+}
+
+fn narrow<C: Coord>(col: i128, row: i128) -> Option<(C, C)> {
+    Some((C::narrow(col)?, C::narrow(row)?))
+}
+
+impl<C: Coord> Position6Offset<C> {
+    /// `None` if the hex lies outside the offset coordinate range.
+    pub fn from_axial(kind: OffsetKind, p: &Position6Axial<C>) -> Option<Position6Offset<C>> {
+        let (q, r) = (p.x.widen(), p.y.widen());
+        // (n - (n & 1)) / 2 rounds down, (n + (n & 1)) / 2 rounds up
+        let (col, row) = match kind {
+            OffsetKind::OddR => (q + (r - (r & 1)) / 2, r),
+            OffsetKind::EvenR => (q + (r + (r & 1)) / 2, r),
+            OffsetKind::OddQ => (q, r + (q - (q & 1)) / 2),
+            OffsetKind::EvenQ => (q, r + (q + (q & 1)) / 2),
+        };
+        let (col, row) = narrow(col, row)?;
+        Some(Position6Offset { kind, col, row })
+    }
+
+    /// `None` if the hex lies outside the axial coordinate range.
+    pub fn to_axial(&self) -> Option<Position6Axial<C>> {
+        let (col, row) = (self.col.widen(), self.row.widen());
+        let (x, y) = match self.kind {
+            OffsetKind::OddR => (col - (row - (row & 1)) / 2, row),
+            OffsetKind::EvenR => (col - (row + (row & 1)) / 2, row),
+            OffsetKind::OddQ => (col, row - (col - (col & 1)) / 2),
+            OffsetKind::EvenQ => (col, row - (col + (col & 1)) / 2),
+        };
+        let (x, y) = narrow(x, y)?;
+        Some(Position6Axial { x, y })
+    }
+}
+
+impl<C: Coord> Position6Doubled<C> {
+    /// `None` if the hex lies outside the doubled coordinate range.
+    pub fn from_axial(kind: DoubledKind, p: &Position6Axial<C>) -> Option<Position6Doubled<C>> {
+        let (q, r) = (p.x.widen(), p.y.widen());
+        let (col, row) = match kind {
+            DoubledKind::Width => (2 * q + r, r),
+            DoubledKind::Height => (q, 2 * r + q),
+        };
+        let (col, row) = narrow(col, row)?;
+        Some(Position6Doubled { kind, col, row })
+    }
+
+    /// `None` for odd `col + row`, which is no hex.
+    pub fn to_axial(&self) -> Option<Position6Axial<C>> {
+        let (col, row) = (self.col.widen(), self.row.widen());
+        if (col + row) & 1 != 0 {
+            return None;
+        }
+        let (x, y) = match self.kind {
+            DoubledKind::Width => ((col - row) / 2, row),
+            DoubledKind::Height => (col, (row - col) / 2),
+        };
+        let (x, y) = narrow(x, y)?;
+        Some(Position6Axial { x, y })
+    }
+}
+
+impl<C: Coord> TryFrom<Position6Offset<C>> for Position6Axial<C> {
+    type Error = Position6Offset<C>;
+
+    fn try_from(p: Position6Offset<C>) -> Result<Self, Position6Offset<C>> {
+        p.to_axial().ok_or(p)
+    }
+}
+
+impl<C: Coord> TryFrom<Position6Doubled<C>> for Position6Axial<C> {
+    type Error = Position6Doubled<C>;
+
+    fn try_from(p: Position6Doubled<C>) -> Result<Self, Position6Doubled<C>> {
+        p.to_axial().ok_or(p)
+    }
+}
+
+/// Serde representations of `Position6Axial` as `{"col": .., "row": ..}` in one of the
+/// offset or doubled systems, for `#[serde(with = "map_types::hex_repr::odd_r")]`.
+pub mod hex_repr {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct ColRow<C> {
+        col: C,
+        row: C,
+    }
+
+    macro_rules! repr {
+        ($name:ident, $ty:ident, $kind:expr) => {
+            pub mod $name {
+                use super::ColRow;
+                use crate::{$ty, Coord, Position6Axial};
+                use serde::de::Error;
+                use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+                pub fn serialize<C: Coord, S: Serializer>(
+                    p: &Position6Axial<C>,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    let p = $ty::from_axial($kind, p).ok_or_else(|| {
+                        serde::ser::Error::custom("hex outside the coordinate range")
+                    })?;
+                    ColRow {
+                        col: p.col,
+                        row: p.row,
+                    }
+                    .serialize(serializer)
+                }
+
+                pub fn deserialize<'de, C: Coord, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Position6Axial<C>, D::Error> {
+                    let ColRow { col, row } = ColRow::deserialize(deserializer)?;
+                    $ty {
+                        kind: $kind,
+                        col,
+                        row,
+                    }
+                    .to_axial()
+                    .ok_or_else(|| D::Error::custom("no hex at these coordinates"))
+                }
+            }
+        };
+    }
+
+    repr!(odd_r, Position6Offset, crate::OffsetKind::OddR);
+    repr!(even_r, Position6Offset, crate::OffsetKind::EvenR);
+    repr!(odd_q, Position6Offset, crate::OffsetKind::OddQ);
+    repr!(even_q, Position6Offset, crate::OffsetKind::EvenQ);
+    repr!(doubled_width, Position6Doubled, crate::DoubledKind::Width);
+    repr!(doubled_height, Position6Doubled, crate::DoubledKind::Height);
+}
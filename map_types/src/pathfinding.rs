@@ -0,0 +1,254 @@
+use crate::{Adjacent, Borders, Direction, Metric, Natural, Position};
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+/// What moving across a map costs.
+pub trait Terrain<P> {
+    /// Cost of stepping from `from` onto its neighbour `to`, `None` if the step is blocked.
+    fn cost(&self, from: &P, to: &P) -> Option<u32>;
+
+    /// A lower bound for the cost of any step, scales the heuristic of `astar`. Metrics
+    /// multiply the costs with their own `step_cost`.
+    fn min_cost(&self) -> u32 {
+        1
+    }
+}
+
+/// Blocked tiles, blocked edges between tiles and per-tile costs. Entering a tile costs
+/// `default_cost` unless set otherwise.
+#[derive(Debug, Clone)]
+pub struct Obstacles<P: Eq + Hash> {
+    pub default_cost: u32,
+    blocked: HashSet<P>,
+    walls: HashSet<(P, P)>,
+    costs: HashMap<P, u32>,
+}
+
+impl<P: Eq + Hash + Clone> Obstacles<P> {
+    pub fn new(default_cost: u32) -> Self {
+        Obstacles {
+            default_cost,
+            blocked: HashSet::new(),
+            walls: HashSet::new(),
+            costs: HashMap::new(),
+        }
+    }
+
+    pub fn block_tile(&mut self, tile: P) {
+        self.blocked.insert(tile);
+    }
+
+    /// Blocks moving between two neighbours, in both directions.
+    pub fn block_edge(&mut self, a: P, b: P) {
+        self.walls.insert((b.clone(), a.clone()));
+        self.walls.insert((a, b));
+    }
+
+    pub fn set_cost(&mut self, tile: P, cost: u32) {
+        self.costs.insert(tile, cost);
+    }
+
+    pub fn is_blocked(&self, tile: &P) -> bool {
+        self.blocked.contains(tile)
+    }
+
+    pub fn is_wall(&self, a: &P, b: &P) -> bool {
+        self.walls.contains(&(a.clone(), b.clone()))
+    }
+}
+
+impl<P: Eq + Hash + Clone> Obstacles<P> {
+    /// Blocks the edges `borders` marks as walls.
+    pub fn block_borders<B: Borders>(&mut self, tile: &P, borders: &B)
+    where
+        P: Adjacent<B::Direction>,
+    {
+        for side in B::Direction::iter().filter(|&side| borders.is_wall(side)) {
+            if let Some(neighbour) = tile.checked_neighbour(side) {
+                self.block_edge(tile.clone(), neighbour);
+            }
+        }
+    }
+}
+
+impl<P: Eq + Hash + Clone> Terrain<P> for Obstacles<P> {
+    fn cost(&self, from: &P, to: &P) -> Option<u32> {
+        if self.is_blocked(to) || self.is_wall(from, to) {
+            return None;
+        }
+        Some(*self.costs.get(to).unwrap_or(&self.default_cost))
+    }
+
+    fn min_cost(&self) -> u32 {
+        self.costs
+            .values()
+            .copied()
+            .fold(self.default_cost, u32::min)
+    }
+}
+
+/// The tiles from start to goal, both included, and the summed cost of the steps.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Path<P> {
+    pub tiles: Vec<P>,
+    pub cost: u32,
+}
+
+struct Node<P> {
+    position: P,
+    cost: u32,
+    parent: Option<usize>,
+}
+
+/// Best-first search from `start`. Visits tiles in order of cost plus `heuristic`, until
+/// `done` accepts one or nothing cheaper than `budget` is left.
+fn search<P, T, M, H, D>(
+    start: &P,
+    terrain: &T,
+    metric: &M,
+    budget: u32,
+    heuristic: H,
+    mut done: D,
+) -> (Vec<Node<P>>, Option<usize>)
+where
+    P: Eq + Hash + Clone,
+    T: Terrain<P> + ?Sized,
+    M: Metric<P> + ?Sized,
+    H: Fn(&P) -> u32,
+    D: FnMut(&P) -> bool,
+{
+    let mut nodes = vec![Node {
+        position: start.clone(),
+        cost: 0,
+        parent: None,
+    }];
+    let mut index = HashMap::new();
+    index.insert(start.clone(), 0);
+    let mut closed = HashSet::new();
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((heuristic(start), 0u32, 0usize)));
+    while let Some(Reverse((_, cost, i))) = open.pop() {
+        if cost > nodes[i].cost || !closed.insert(i) {
+            continue;
+        }
+        if done(&nodes[i].position) {
+            return (nodes, Some(i));
+        }
+        for next in metric.neighbours(&nodes[i].position) {
+            let step = match terrain.cost(&nodes[i].position, &next) {
+                Some(step) => step.saturating_mul(metric.step_cost(&nodes[i].position, &next)),
+                None => continue,
+            };
+            let cost = match cost.checked_add(step) {
+                Some(cost) if cost <= budget => cost,
+                _ => continue,
+            };
+            let j = match index.entry(next.clone()) {
+                Entry::Occupied(e) => {
+                    let j = *e.get();
+                    if nodes[j].cost <= cost {
+                        continue;
+                    }
+                    nodes[j].cost = cost;
+                    nodes[j].parent = Some(i);
+                    j
+                }
+                Entry::Vacant(e) => {
+                    nodes.push(Node {
+                        position: next.clone(),
+                        cost,
+                        parent: Some(i),
+                    });
+                    *e.insert(nodes.len() - 1)
+                }
+            };
+            open.push(Reverse((cost.saturating_add(heuristic(&next)), cost, j)));
+        }
+    }
+    (nodes, None)
+}
+
+fn path_to<P: Clone>(nodes: &[Node<P>], end: usize) -> Path<P> {
+    let mut tiles = Vec::new();
+    let mut current = Some(end);
+    while let Some(i) = current {
+        tiles.push(nodes[i].position.clone());
+        current = nodes[i].parent;
+    }
+    tiles.reverse();
+    Path {
+        tiles,
+        cost: nodes[end].cost,
+    }
+}
+
+/// Cheapest path using `field_length` times `Terrain::min_cost` as the heuristic.
+pub fn astar<P, T>(start: &P, goal: &P, terrain: &T) -> Option<Path<P>>
+where
+    P: Position<P> + Eq + Hash + Clone,
+    T: Terrain<P> + ?Sized,
+{
+    astar_with(start, goal, terrain, &Natural)
+}
+
+/// Cheapest path moving and pricing steps by `metric`, using its `cost_estimate` times
+/// `Terrain::min_cost` as the heuristic.
+pub fn astar_with<P, T, M>(start: &P, goal: &P, terrain: &T, metric: &M) -> Option<Path<P>>
+where
+    P: Eq + Hash + Clone,
+    T: Terrain<P> + ?Sized,
+    M: Metric<P> + ?Sized,
+{
+    let min_cost = terrain.min_cost();
+    let (nodes, end) = search(
+        start,
+        terrain,
+        metric,
+        u32::MAX,
+        |p| metric.cost_estimate(p, goal).saturating_mul(min_cost),
+        |p| p == goal,
+    );
+    end.map(|end| path_to(&nodes, end))
+}
+
+/// Cheapest path without a heuristic, for terrains where `min_cost` can not be given.
+pub fn dijkstra<P, T>(start: &P, goal: &P, terrain: &T) -> Option<Path<P>>
+where
+    P: Position<P> + Eq + Hash + Clone,
+    T: Terrain<P> + ?Sized,
+{
+    dijkstra_with(start, goal, terrain, &Natural)
+}
+
+/// Like `dijkstra`, moving and pricing steps by `metric`.
+pub fn dijkstra_with<P, T, M>(start: &P, goal: &P, terrain: &T, metric: &M) -> Option<Path<P>>
+where
+    P: Eq + Hash + Clone,
+    T: Terrain<P> + ?Sized,
+    M: Metric<P> + ?Sized,
+{
+    let (nodes, end) = search(start, terrain, metric, u32::MAX, |_| 0, |p| p == goal);
+    end.map(|end| path_to(&nodes, end))
+}
+
+/// Every tile reachable from `start` for at most `budget`, with the cost of reaching it.
+pub fn reachable<P, T>(start: &P, terrain: &T, budget: u32) -> HashMap<P, u32>
+where
+    P: Position<P> + Eq + Hash + Clone,
+    T: Terrain<P> + ?Sized,
+{
+    reachable_with(start, terrain, budget, &Natural)
+}
+
+/// Like `reachable`, moving and pricing steps by `metric`.
+pub fn reachable_with<P, T, M>(start: &P, terrain: &T, budget: u32, metric: &M) -> HashMap<P, u32>
+where
+    P: Eq + Hash + Clone,
+    T: Terrain<P> + ?Sized,
+    M: Metric<P> + ?Sized,
+{
+    let (nodes, _) = search(start, terrain, metric, budget, |_| 0, |_| false);
+    nodes.into_iter().map(|n| (n.position, n.cost)).collect()
+}
@@ -1,169 +1,540 @@
+use crate::coord::saturate;
+use crate::{Adjacent, Coord, Direction, Direction4, Direction6};
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
-use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 use std::ops::{Add, AddAssign, Sub, SubAssign, Neg};
-use structured_digest::Digestable;
-
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Position4 {
-    pub x: i16,
-    pub y: i16,
-}
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
-pub struct Position6Axial {
-    pub x: i16,
-    pub y: i16,
-}
-impl PartialEq<Position6Cube> for Position6Axial {
-    fn eq(&self, other: &Position6Cube) -> bool {
+use structured_digest::{Decode, Digestable, HasherSink, Version};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash, Digestable, Decode)]
+#[serde(into = "repr::Repr2<C>", try_from = "repr::Repr2<C>", bound = "")]
+#[digest(tag = "map_types::Position4", version = C::POSITION_SCHEMA)]
+pub struct Position4<C: Coord = i16> {
+    pub x: C,
+    pub y: C,
+}
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Digestable, Decode)]
+#[serde(into = "repr::Repr2<C>", try_from = "repr::Repr2<C>", bound = "")]
+#[digest(tag = "map_types::Position6Axial", version = C::POSITION_SCHEMA)]
+pub struct Position6Axial<C: Coord = i16> {
+    pub x: C,
+    pub y: C,
+}
+impl<C: Coord> PartialEq<Position6Cube<C>> for Position6Axial<C> {
+    fn eq(&self, other: &Position6Cube<C>) -> bool {
         self.x == other.x && self.y == other.y
     }
 }
-impl Hash for Position6Axial {
+impl<C: Coord> Hash for Position6Axial<C> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.x.hash(state);
-        self.y.hash(state);
+        self.update_le(&mut HasherSink(state), Version::V1)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
-pub struct Position6Cube {
-    pub x: i16,
-    pub y: i16,
-    pub z: i16,
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Digestable, Decode)]
+#[serde(into = "repr::Repr3<C>", try_from = "repr::Repr3<C>", bound = "")]
+#[digest(tag = "map_types::Position6Cube", version = C::POSITION_SCHEMA)]
+pub struct Position6Cube<C: Coord = i16> {
+    pub x: C,
+    pub y: C,
+    #[digest(with = cube_z)]
+    pub z: C,
 }
-impl PartialEq<Position6Axial> for Position6Cube {
-    fn eq(&self, other: &Position6Axial) -> bool {
+impl<C: Coord> PartialEq<Position6Axial<C>> for Position6Cube<C> {
+    fn eq(&self, other: &Position6Axial<C>) -> bool {
         self.x == other.x && self.y == other.y
     }
 }
-impl Hash for Position6Cube {
+impl<C: Coord> Hash for Position6Cube<C> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.x.hash(state);
-        self.y.hash(state);
+        self.update_le(&mut HasherSink(state), Version::V1)
+    }
+}
+
+/// A triangle of a triangle grid. The rhombus `(x, y)`, spanned by two axes 60° apart with y
+/// pointing down as on screen, splits into a down and an up triangle.
+///
+/// Arithmetic treats positions as offsets from the down triangle at the origin: coordinates add,
+/// and adding an up triangle switches to the other triangle of the rhombus.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash, Default, Digestable, Decode)]
+#[serde(into = "repr::ReprTriangle<C>", try_from = "repr::ReprTriangle<C>", bound = "")]
+#[digest(tag = "map_types::Position3", version = C::POSITION_SCHEMA)]
+pub struct Position3<C: Coord = i16> {
+    pub x: C,
+    pub y: C,
+    pub up: bool,
+}
+
+/// `Version::V0` left out z, ids hashed with it have to stay reproducible.
+mod cube_z {
+    use crate::Coord;
+    use structured_digest::{DecodeError, Encoding, Reader, Sink, Version};
+
+    pub fn digest<C: Coord, S: Sink + ?Sized>(z: &C, sink: &mut S, encoding: Encoding) {
+        if encoding.version != Version::V0 {
+            z.digest(sink, encoding)
+        }
+    }
+
+    pub fn decode<C: Coord>(reader: &mut Reader<'_>) -> Result<C, DecodeError> {
+        C::decode(reader)
+    }
+}
+
+/// Serialized positions name their width, unless it is `i16` which came first.
+mod repr {
+    use crate::{Coord, Position3, Position4, Position6Axial, Position6Cube, WidthMismatch};
+    use serde::{Deserialize, Serialize};
+    use std::convert::TryFrom;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Repr2<C> {
+        x: C,
+        y: C,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bits: Option<u32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Repr3<C> {
+        x: C,
+        y: C,
+        z: C,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bits: Option<u32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ReprTriangle<C> {
+        x: C,
+        y: C,
+        up: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bits: Option<u32>,
+    }
+
+    fn bits<C: Coord>() -> Option<u32> {
+        if C::BITS == 16 {
+            None
+        } else {
+            Some(C::BITS)
+        }
+    }
+
+    fn check<C: Coord>(bits: Option<u32>) -> Result<(), WidthMismatch> {
+        let found = bits.unwrap_or(16);
+        if found == C::BITS {
+            Ok(())
+        } else {
+            Err(WidthMismatch {
+                expected: C::BITS,
+                found,
+            })
+        }
+    }
+
+    impl<C: Coord> From<Position4<C>> for Repr2<C> {
+        fn from(p: Position4<C>) -> Self {
+            Repr2 {
+                x: p.x,
+                y: p.y,
+                bits: bits::<C>(),
+            }
+        }
+    }
+
+    impl<C: Coord> TryFrom<Repr2<C>> for Position4<C> {
+        type Error = WidthMismatch;
+
+        fn try_from(r: Repr2<C>) -> Result<Self, WidthMismatch> {
+            check::<C>(r.bits)?;
+            Ok(Position4 { x: r.x, y: r.y })
+        }
+    }
+
+    impl<C: Coord> From<Position6Axial<C>> for Repr2<C> {
+        fn from(p: Position6Axial<C>) -> Self {
+            Repr2 {
+                x: p.x,
+                y: p.y,
+                bits: bits::<C>(),
+            }
+        }
+    }
+
+    impl<C: Coord> TryFrom<Repr2<C>> for Position6Axial<C> {
+        type Error = WidthMismatch;
+
+        fn try_from(r: Repr2<C>) -> Result<Self, WidthMismatch> {
+            check::<C>(r.bits)?;
+            Ok(Position6Axial { x: r.x, y: r.y })
+        }
+    }
+
+    impl<C: Coord> From<Position6Cube<C>> for Repr3<C> {
+        fn from(p: Position6Cube<C>) -> Self {
+            Repr3 {
+                x: p.x,
+                y: p.y,
+                z: p.z,
+                bits: bits::<C>(),
+            }
+        }
+    }
+
+    impl<C: Coord> TryFrom<Repr3<C>> for Position6Cube<C> {
+        type Error = WidthMismatch;
+
+        fn try_from(r: Repr3<C>) -> Result<Self, WidthMismatch> {
+            check::<C>(r.bits)?;
+            Ok(Position6Cube {
+                x: r.x,
+                y: r.y,
+                z: r.z,
+            })
+        }
+    }
+    impl<C: Coord> From<Position3<C>> for ReprTriangle<C> {
+        fn from(p: Position3<C>) -> Self {
+            ReprTriangle {
+                x: p.x,
+                y: p.y,
+                up: p.up,
+                bits: bits::<C>(),
+            }
+        }
+    }
+
+    impl<C: Coord> TryFrom<ReprTriangle<C>> for Position3<C> {
+        type Error = WidthMismatch;
+
+        fn try_from(r: ReprTriangle<C>) -> Result<Self, WidthMismatch> {
+            check::<C>(r.bits)?;
+            Ok(Position3 {
+                x: r.x,
+                y: r.y,
+                up: r.up,
+            })
+        }
+    }
+}
+
+/// A serialized position was written with a different coordinate width.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WidthMismatch {
+    pub expected: u32,
+    pub found: u32,
+}
+
+impl Display for WidthMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {} bit coordinates, found {} bit", self.expected, self.found)
     }
 }
 
+impl Error for WidthMismatch {}
+
+/// Neighbour directions and other small offsets fit into every width.
+pub(crate) fn step<C: Coord>(d: i8) -> C {
+    C::narrow(d.into()).expect("i8 fits every width")
+}
+
 pub trait PositionHelper:Sized {
+/// The tiles across each edge. On square and hex grids clockwise in the order of `Direction4::ALL`
+/// and `Direction6::ALL`.
 fn neighbours(&self)->Vec<Self>;
+/// The neighbours that can be represented, fewer at the edge of the coordinate range.
+fn checked_neighbours(&self)->Vec<Self>;
+/// `None` if a coordinate would overflow.
+fn checked_add(&self,rhs:&Self)->Option<Self>;
+fn checked_sub(&self,rhs:&Self)->Option<Self>;
+fn checked_neg(&self)->Option<Self>;
 }
 
+/// Distances saturate at `u32::MAX`, which only wide coordinates reach.
 pub trait Position<T: Position<T>>: Add<T> + AddAssign<T> + Sub<T> + SubAssign<T> + Neg + PositionHelper + Digestable {
     fn field_length(&self, other: &T) -> u32;
     fn line_length(&self, other: &T) -> f64;
 }
 
-impl PositionHelper for Position4{
+impl<C: Coord> PositionHelper for Position4<C>{
     fn neighbours(&self)->Vec<Self> {
-        vec![
-            self.clone()+Position4::from((1,0)),
-            self.clone()+Position4::from((0,1)),
-            self.clone()+Position4::from((-1,0)),
-            self.clone()+Position4::from((0,-1))
-        ]
+        Direction4::iter().map(|d| self.neighbour(d)).collect()
+    }
+
+    fn checked_neighbours(&self)->Vec<Self> {
+        Direction4::iter().filter_map(|d| self.checked_neighbour(d)).collect()
+    }
+
+    fn checked_add(&self,rhs:&Self)->Option<Self> {
+        Some(Position4{
+            x:self.x.checked_add(rhs.x)?,
+            y:self.y.checked_add(rhs.y)?
+        })
+    }
+
+    fn checked_sub(&self,rhs:&Self)->Option<Self> {
+        Some(Position4{
+            x:self.x.checked_sub(rhs.x)?,
+            y:self.y.checked_sub(rhs.y)?
+        })
+    }
+
+    fn checked_neg(&self)->Option<Self> {
+        Some(Position4{
+            x:self.x.checked_neg()?,
+            y:self.y.checked_neg()?
+        })
     }
 }
 
-impl Position<Position4> for Position4 {
-    fn field_length(&self, other: &Position4) -> u32 {
-        u32::try_from(
-            (i32::from(self.x) - i32::from(other.x)).abs()
-                + (i32::from(self.y) - i32::from(other.y)),
-        )
-            .expect("Result should be >=0")
+impl<C: Coord> Position4<C>{
+    pub fn saturating_add(self,rhs:Position4<C>)->Position4<C>{
+        Position4{
+            x:self.x.saturating_add(rhs.x),
+            y:self.y.saturating_add(rhs.y)
+        }
+    }
+
+    pub fn saturating_sub(self,rhs:Position4<C>)->Position4<C>{
+        Position4{
+            x:self.x.saturating_sub(rhs.x),
+            y:self.y.saturating_sub(rhs.y)
+        }
     }
+}
 
-    fn line_length(&self, other: &Position4) -> f64 {
-        f64::from(
-            (i32::from(self.x) - i32::from(other.x)) * (i32::from(self.x) - i32::from(other.x))
-                + (i32::from(self.y) - i32::from(other.y))
-                * (i32::from(self.y) - i32::from(other.y)),
+impl<C: Coord> Position<Position4<C>> for Position4<C> {
+    fn field_length(&self, other: &Position4<C>) -> u32 {
+        saturate(
+            (self.x.widen() - other.x.widen()).abs()
+                + (self.y.widen() - other.y.widen()).abs(),
         )
-            .sqrt()
+    }
+
+    fn line_length(&self, other: &Position4<C>) -> f64 {
+        let dx = (self.x.widen() - other.x.widen()) as f64;
+        let dy = (self.y.widen() - other.y.widen()) as f64;
+        (dx * dx + dy * dy).sqrt()
     }
 }
 
-impl PositionHelper for Position6Axial{
+impl<C: Coord> PositionHelper for Position6Axial<C>{
     fn neighbours(&self)->Vec<Self> {
-        vec![
-            self.clone()+Position6Axial::from((1,0)),
-            self.clone()+Position6Axial::from((0,1)),
-            self.clone()+Position6Axial::from((-1,1)),
-            self.clone()+Position6Axial::from((-1,0)),
-            self.clone()+Position6Axial::from((0,-1)),
-            self.clone()+Position6Axial::from((1,-1))
-        ]
+        Direction6::iter().map(|d| self.neighbour(d)).collect()
+    }
+
+    fn checked_neighbours(&self)->Vec<Self> {
+        Direction6::iter().filter_map(|d| self.checked_neighbour(d)).collect()
+    }
+
+    fn checked_add(&self,rhs:&Self)->Option<Self> {
+        Some(Position6Axial{
+            x:self.x.checked_add(rhs.x)?,
+            y:self.y.checked_add(rhs.y)?
+        })
+    }
+
+    fn checked_sub(&self,rhs:&Self)->Option<Self> {
+        Some(Position6Axial{
+            x:self.x.checked_sub(rhs.x)?,
+            y:self.y.checked_sub(rhs.y)?
+        })
+    }
+
+    fn checked_neg(&self)->Option<Self> {
+        Some(Position6Axial{
+            x:self.x.checked_neg()?,
+            y:self.y.checked_neg()?
+        })
+    }
+}
+
+impl<C: Coord> Position6Axial<C>{
+    pub fn saturating_add(self,rhs:Position6Axial<C>)->Position6Axial<C>{
+        Position6Axial{
+            x:self.x.saturating_add(rhs.x),
+            y:self.y.saturating_add(rhs.y)
+        }
+    }
+
+    pub fn saturating_sub(self,rhs:Position6Axial<C>)->Position6Axial<C>{
+        Position6Axial{
+            x:self.x.saturating_sub(rhs.x),
+            y:self.y.saturating_sub(rhs.y)
+        }
+    }
+
+    /// `None` if z does not fit, which happens far from the origin.
+    pub fn checked_to_cube(&self)->Option<Position6Cube<C>>{
+        Some(Position6Cube{
+            x:self.x,
+            y:self.y,
+            z:C::narrow(-(self.x.widen()+self.y.widen()))?
+        })
     }
 }
 
-impl Position<Position6Axial> for Position6Axial {
-    fn field_length(&self, other: &Position6Axial) -> u32 {
-        self.to_cube().field_length(other.to_cube().borrow())
+impl<C: Coord> Position<Position6Axial<C>> for Position6Axial<C> {
+    // computed wide, the cube coordinates of both may not fit
+    fn field_length(&self, other: &Position6Axial<C>) -> u32 {
+        let dx = self.x.widen() - other.x.widen();
+        let dy = self.y.widen() - other.y.widen();
+        saturate((dx.abs() + dy.abs() + (dx + dy).abs()) / 2)
     }
 
-    fn line_length(&self, other: &Position6Axial) -> f64 {
-        let dx = i32::from(self.x - other.x);
-        let dy = i32::from(self.y - other.y);
-        f64::from(dx * dx + dy * dy + dx * dy).sqrt()
+    fn line_length(&self, other: &Position6Axial<C>) -> f64 {
+        let dx = (self.x.widen() - other.x.widen()) as f64;
+        let dy = (self.y.widen() - other.y.widen()) as f64;
+        (dx * dx + dy * dy + dx * dy).sqrt()
     }
 }
 
-impl PositionHelper for Position6Cube{
+impl<C: Coord> PositionHelper for Position6Cube<C>{
     fn neighbours(&self)->Vec<Self> {
-        vec![
-            self.clone()+Position6Cube::from((1,0,-1)),
-            self.clone()+Position6Cube::from((0,1,-1)),
-            self.clone()+Position6Cube::from((-1,1,0)),
-            self.clone()+Position6Cube::from((-1,0,1)),
-            self.clone()+Position6Cube::from((0,-1,1)),
-            self.clone()+Position6Cube::from((1,-1,0))
-        ]
+        Direction6::iter().map(|d| self.neighbour(d)).collect()
+    }
+
+    fn checked_neighbours(&self)->Vec<Self> {
+        Direction6::iter().filter_map(|d| self.checked_neighbour(d)).collect()
+    }
+
+    fn checked_add(&self,rhs:&Self)->Option<Self> {
+        Some(Position6Cube{
+            x:self.x.checked_add(rhs.x)?,
+            y:self.y.checked_add(rhs.y)?,
+            z:self.z.checked_add(rhs.z)?
+        })
+    }
+
+    fn checked_sub(&self,rhs:&Self)->Option<Self> {
+        Some(Position6Cube{
+            x:self.x.checked_sub(rhs.x)?,
+            y:self.y.checked_sub(rhs.y)?,
+            z:self.z.checked_sub(rhs.z)?
+        })
+    }
+
+    fn checked_neg(&self)->Option<Self> {
+        Some(Position6Cube{
+            x:self.x.checked_neg()?,
+            y:self.y.checked_neg()?,
+            z:self.z.checked_neg()?
+        })
     }
 }
 
-impl Position<Position6Cube> for Position6Cube {
-    fn field_length(&self, other: &Position6Cube) -> u32 {
-        u32::try_from(
-            (i32::from(self.x) - i32::from(other.x)).abs()
-                + (i32::from(self.y) - i32::from(other.y)).abs()
-                + (i32::from(self.z) - i32::from(other.z)).abs(),
+impl<C: Coord> Position<Position6Cube<C>> for Position6Cube<C> {
+    fn field_length(&self, other: &Position6Cube<C>) -> u32 {
+        saturate(
+            ((self.x.widen() - other.x.widen()).abs()
+                + (self.y.widen() - other.y.widen()).abs()
+                + (self.z.widen() - other.z.widen()).abs())
+                / 2,
         )
-            .expect("Result should be >=0")
     }
 
-    fn line_length(&self, other: &Position6Cube) -> f64 {
+    fn line_length(&self, other: &Position6Cube<C>) -> f64 {
         self.to_axial().line_length(other.to_axial().borrow())
     }
 }
 
-impl Position<Position6Cube> for Position6Axial {
-    fn field_length(&self, other: &Position6Cube) -> u32 {
-        self.to_cube().field_length(other)
+impl<C: Coord> Position<Position6Cube<C>> for Position6Axial<C> {
+    fn field_length(&self, other: &Position6Cube<C>) -> u32 {
+        self.field_length(other.to_axial().borrow())
     }
 
-    fn line_length(&self, other: &Position6Cube) -> f64 {
+    fn line_length(&self, other: &Position6Cube<C>) -> f64 {
         self.line_length(other.to_axial().borrow())
     }
 }
 
-impl Position<Position6Axial> for Position6Cube {
-    fn field_length(&self, other: &Position6Axial) -> u32 {
-        self.field_length(other.to_cube().borrow())
+impl<C: Coord> Position<Position6Axial<C>> for Position6Cube<C> {
+    fn field_length(&self, other: &Position6Axial<C>) -> u32 {
+        self.to_axial().field_length(other)
     }
 
-    fn line_length(&self, other: &Position6Axial) -> f64 {
+    fn line_length(&self, other: &Position6Axial<C>) -> f64 {
         self.to_axial().line_length(other)
     }
 }
 
-pub trait Position6: From<Position6Axial> + From<Position6Cube> {
-    fn to_axial(&self) -> Position6Axial;
-    fn to_cube(&self) -> Position6Cube;
+impl<C: Coord> PositionHelper for Position3<C>{
+    /// The triangle across each edge: the other half of the rhombus first, then the
+    /// neighbours along x and y.
+    fn neighbours(&self)->Vec<Self> {
+        let d=if self.up {step(1)} else {step(-1)};
+        vec![
+            *self+Position3::from((C::ZERO,C::ZERO,true)),
+            *self+Position3::from((d,C::ZERO,true)),
+            *self+Position3::from((C::ZERO,d,true))
+        ]
+    }
+
+    fn checked_neighbours(&self)->Vec<Self> {
+        let d=if self.up {1} else {-1};
+        [(0,0),(d,0),(0,d)]
+            .iter()
+            .filter_map(|&(x,y)| self.checked_add(&Position3::from((step(x),step(y),true))))
+            .collect()
+    }
+
+    fn checked_add(&self,rhs:&Self)->Option<Self> {
+        Some(Position3{
+            x:self.x.checked_add(rhs.x)?,
+            y:self.y.checked_add(rhs.y)?,
+            up:self.up!=rhs.up
+        })
+    }
+
+    fn checked_sub(&self,rhs:&Self)->Option<Self> {
+        Some(Position3{
+            x:self.x.checked_sub(rhs.x)?,
+            y:self.y.checked_sub(rhs.y)?,
+            up:self.up!=rhs.up
+        })
+    }
+
+    fn checked_neg(&self)->Option<Self> {
+        Some(Position3{
+            x:self.x.checked_neg()?,
+            y:self.y.checked_neg()?,
+            up:self.up
+        })
+    }
+}
+
+impl<C: Coord> Position<Position3<C>> for Position3<C> {
+    // every edge crossed is a line of one of the three families x, y and x + y
+    fn field_length(&self, other: &Position3<C>) -> u32 {
+        let dx = self.x.widen() - other.x.widen();
+        let dy = self.y.widen() - other.y.widen();
+        let ds = dx + dy + i128::from(self.up) - i128::from(other.up);
+        saturate(dx.abs() + dy.abs() + ds.abs())
+    }
+
+    // between centroids, scaled so neighbours are 1 apart
+    fn line_length(&self, other: &Position3<C>) -> f64 {
+        let third = |up: bool| if up { 2.0 / 3.0 } else { 1.0 / 3.0 };
+        let dx = (self.x.widen() - other.x.widen()) as f64 + third(self.up) - third(other.up);
+        let dy = (self.y.widen() - other.y.widen()) as f64 + third(self.up) - third(other.up);
+        (3.0 * (dx * dx + dy * dy + dx * dy)).sqrt()
+    }
+}
+
+/// Axial and cube hex positions, convertible into each other.
+pub trait Position6: Sized {
+    type Coord: Coord;
+
+    fn to_axial(&self) -> Position6Axial<Self::Coord>;
+    fn to_cube(&self) -> Position6Cube<Self::Coord>;
+    fn from_cube(cube: Position6Cube<Self::Coord>) -> Self;
 }
 
-impl From<Position6Axial> for Position6Cube {
-    fn from(other: Position6Axial) -> Self {
+impl<C: Coord> From<Position6Axial<C>> for Position6Cube<C> {
+    fn from(other: Position6Axial<C>) -> Self {
         Position6Cube {
             x: other.x,
             y: other.y,
@@ -172,8 +543,8 @@ impl From<Position6Axial> for Position6Cube {
     }
 }
 
-impl From<Position6Cube> for Position6Axial {
-    fn from(other: Position6Cube) -> Self {
+impl<C: Coord> From<Position6Cube<C>> for Position6Axial<C> {
+    fn from(other: Position6Cube<C>) -> Self {
         Position6Axial {
             x: other.x,
             y: other.y,
@@ -181,37 +552,70 @@ impl From<Position6Cube> for Position6Axial {
     }
 }
 
-impl Position6 for Position6Axial {
-    fn to_axial(&self) -> Position6Axial {
-        self.clone()
+impl<C: Coord> Position6 for Position6Axial<C> {
+    type Coord = C;
+
+    fn to_axial(&self) -> Position6Axial<C> {
+        *self
     }
 
-    fn to_cube(&self) -> Position6Cube {
+    fn to_cube(&self) -> Position6Cube<C> {
         Position6Cube {
             x: self.x,
             y: self.y,
             z: -(self.x + self.y),
         }
     }
+
+    fn from_cube(cube: Position6Cube<C>) -> Self {
+        cube.into()
+    }
 }
 
-impl Position6 for Position6Cube {
-    fn to_axial(&self) -> Position6Axial {
+impl<C: Coord> Position6 for Position6Cube<C> {
+    type Coord = C;
+
+    fn to_axial(&self) -> Position6Axial<C> {
         Position6Axial {
             x: self.x,
             y: self.y,
         }
     }
 
-    fn to_cube(&self) -> Position6Cube {
-        self.clone()
+    fn to_cube(&self) -> Position6Cube<C> {
+        *self
+    }
+
+    fn from_cube(cube: Position6Cube<C>) -> Self {
+        cube
     }
 }
 
-impl Add for Position4 {
-    type Output = Position4;
+impl<C: Coord> Position6Cube<C> {
+    /// The hex containing the fractional cube position, for positions with `x + y + z == 0`.
+    pub fn round(x: f64, y: f64, z: f64) -> Position6Cube<C> {
+        let (rx, ry, rz) = (x.round(), y.round(), z.round());
+        let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+        // the coordinate that moved most is recomputed from the other two
+        let (rx, ry, rz) = if dx > dy && dx > dz {
+            (-ry - rz, ry, rz)
+        } else if dy > dz {
+            (rx, -rx - rz, rz)
+        } else {
+            (rx, ry, -rx - ry)
+        };
+        Position6Cube {
+            x: C::round_f64(rx),
+            y: C::round_f64(ry),
+            z: C::round_f64(rz),
+        }
+    }
+}
+
+impl<C: Coord> Add for Position4<C> {
+    type Output = Position4<C>;
 
-    fn add(self, rhs: Position4) -> Self::Output {
+    fn add(self, rhs: Position4<C>) -> Self::Output {
         Position4 {
             x: self.x + rhs.x,
             y: self.y + rhs.y,
@@ -219,14 +623,14 @@ impl Add for Position4 {
     }
 }
 
-impl AddAssign for Position4 {
+impl<C: Coord> AddAssign for Position4<C> {
     fn add_assign(&mut self, rhs: Self) {
         self.x += rhs.x;
         self.y += rhs.y;
     }
 }
-impl Sub for Position4 {
-    type Output = Position4;
+impl<C: Coord> Sub for Position4<C> {
+    type Output = Position4<C>;
 
     fn sub(self, rhs: Self) -> Self::Output {
         Position4 {
@@ -235,15 +639,15 @@ impl Sub for Position4 {
         }
     }
 }
-impl SubAssign for Position4 {
+impl<C: Coord> SubAssign for Position4<C> {
     fn sub_assign(&mut self, rhs: Self) {
         self.x -= rhs.x;
         self.y -= rhs.y;
     }
 }
 
-impl Add for Position6Axial {
-    type Output = Position6Axial;
+impl<C: Coord> Add for Position6Axial<C> {
+    type Output = Position6Axial<C>;
 
     fn add(self, rhs: Self) -> Self::Output {
         Position6Axial {
@@ -252,14 +656,14 @@ impl Add for Position6Axial {
         }
     }
 }
-impl AddAssign for Position6Axial {
+impl<C: Coord> AddAssign for Position6Axial<C> {
     fn add_assign(&mut self, rhs: Self) {
         self.x += rhs.x;
         self.y += rhs.y;
     }
 }
-impl Sub for Position6Axial {
-    type Output = Position6Axial;
+impl<C: Coord> Sub for Position6Axial<C> {
+    type Output = Position6Axial<C>;
 
     fn sub(self, rhs: Self) -> Self::Output {
         Position6Axial {
@@ -268,15 +672,15 @@ impl Sub for Position6Axial {
         }
     }
 }
-impl SubAssign for Position6Axial {
+impl<C: Coord> SubAssign for Position6Axial<C> {
     fn sub_assign(&mut self, rhs: Self) {
         self.x -= rhs.x;
         self.y -= rhs.y;
     }
 }
 
-impl Add for Position6Cube {
-    type Output = Position6Cube;
+impl<C: Coord> Add for Position6Cube<C> {
+    type Output = Position6Cube<C>;
 
     fn add(self, rhs: Self) -> Self::Output {
         Position6Cube {
@@ -286,15 +690,15 @@ impl Add for Position6Cube {
         }
     }
 }
-impl AddAssign for Position6Cube {
+impl<C: Coord> AddAssign for Position6Cube<C> {
     fn add_assign(&mut self, rhs: Self) {
         self.x += rhs.x;
         self.y += rhs.y;
         self.z += rhs.z;
     }
 }
-impl Sub for Position6Cube {
-    type Output = Position6Cube;
+impl<C: Coord> Sub for Position6Cube<C> {
+    type Output = Position6Cube<C>;
 
     fn sub(self, rhs: Self) -> Self::Output {
         Position6Cube {
@@ -304,7 +708,7 @@ impl Sub for Position6Cube {
         }
     }
 }
-impl SubAssign for Position6Cube {
+impl<C: Coord> SubAssign for Position6Cube<C> {
     fn sub_assign(&mut self, rhs: Self) {
         self.x -= rhs.x;
         self.y -= rhs.y;
@@ -312,43 +716,43 @@ impl SubAssign for Position6Cube {
     }
 }
 
-impl Add<Position6Cube> for Position6Axial {
-    type Output = Position6Axial;
+impl<C: Coord> Add<Position6Cube<C>> for Position6Axial<C> {
+    type Output = Position6Axial<C>;
 
-    fn add(self, rhs: Position6Cube) -> Self::Output {
+    fn add(self, rhs: Position6Cube<C>) -> Self::Output {
         Position6Axial{
             x:self.x+rhs.x,
             y:self.y+rhs.y
         }
     }
 }
-impl AddAssign<Position6Cube> for Position6Axial {
-    fn add_assign(&mut self, rhs: Position6Cube) {
+impl<C: Coord> AddAssign<Position6Cube<C>> for Position6Axial<C> {
+    fn add_assign(&mut self, rhs: Position6Cube<C>) {
         self.x+=rhs.x;
         self.y+=rhs.y;
     }
 }
-impl Sub<Position6Cube> for Position6Axial {
-    type Output = Position6Axial;
+impl<C: Coord> Sub<Position6Cube<C>> for Position6Axial<C> {
+    type Output = Position6Axial<C>;
 
-    fn sub(self, rhs: Position6Cube) -> Self::Output {
+    fn sub(self, rhs: Position6Cube<C>) -> Self::Output {
         Position6Axial{
             x:self.x-rhs.x,
             y:self.y-rhs.y
         }
     }
 }
-impl SubAssign<Position6Cube> for Position6Axial {
-    fn sub_assign(&mut self, rhs: Position6Cube) {
+impl<C: Coord> SubAssign<Position6Cube<C>> for Position6Axial<C> {
+    fn sub_assign(&mut self, rhs: Position6Cube<C>) {
         self.x-=rhs.x;
         self.y-=rhs.y;
     }
 }
 
-impl Add<Position6Axial> for Position6Cube {
-    type Output = Position6Cube;
+impl<C: Coord> Add<Position6Axial<C>> for Position6Cube<C> {
+    type Output = Position6Cube<C>;
 
-    fn add(self, rhs: Position6Axial) -> Self::Output {
+    fn add(self, rhs: Position6Axial<C>) -> Self::Output {
         Position6Cube{
             x:self.x+rhs.x,
             y:self.y+rhs.y,
@@ -356,17 +760,17 @@ impl Add<Position6Axial> for Position6Cube {
         }
     }
 }
-impl AddAssign<Position6Axial> for Position6Cube {
-    fn add_assign(&mut self, rhs: Position6Axial) {
+impl<C: Coord> AddAssign<Position6Axial<C>> for Position6Cube<C> {
+    fn add_assign(&mut self, rhs: Position6Axial<C>) {
         self.x+=rhs.x;
         self.y+=rhs.y;
-        self.x-=rhs.x+rhs.y
+        self.z-=rhs.x+rhs.y;
     }
 }
-impl Sub<Position6Axial> for Position6Cube {
-    type Output = Position6Cube;
+impl<C: Coord> Sub<Position6Axial<C>> for Position6Cube<C> {
+    type Output = Position6Cube<C>;
 
-    fn sub(self, rhs: Position6Axial) -> Self::Output {
+    fn sub(self, rhs: Position6Axial<C>) -> Self::Output {
         Position6Cube{
             x:self.x-rhs.x,
             y:self.y-rhs.y,
@@ -374,93 +778,114 @@ impl Sub<Position6Axial> for Position6Cube {
         }
     }
 }
-impl SubAssign<Position6Axial> for Position6Cube {
-    fn sub_assign(&mut self, rhs: Position6Axial) {
+impl<C: Coord> SubAssign<Position6Axial<C>> for Position6Cube<C> {
+    fn sub_assign(&mut self, rhs: Position6Axial<C>) {
         self.x-=rhs.x;
         self.y-=rhs.y;
         self.z+=rhs.x+rhs.y;
     }
 }
 
-impl Neg for Position4{
-    type Output=Position4;
+impl<C: Coord> Add for Position3<C> {
+    type Output = Position3<C>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Position3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            up: self.up != rhs.up,
+        }
+    }
+}
+impl<C: Coord> AddAssign for Position3<C> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.up ^= rhs.up;
+    }
+}
+impl<C: Coord> Sub for Position3<C> {
+    type Output = Position3<C>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Position3 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            up: self.up != rhs.up,
+        }
+    }
+}
+impl<C: Coord> SubAssign for Position3<C> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self.up ^= rhs.up;
+    }
+}
+
+impl<C: Coord> Neg for Position4<C> {
+    type Output=Position4<C>;
 
     fn neg(self) -> Self::Output {
         Position4{
-            x:!self.x,
-            y:!self.y
+            x:-self.x,
+            y:-self.y
         }
     }
 }
-impl Neg for Position6Axial{
-    type Output=Position6Axial;
+impl<C: Coord> Neg for Position6Axial<C> {
+    type Output=Position6Axial<C>;
 
     fn neg(self) -> Self::Output {
         Position6Axial{
-            x:!self.x,
-            y:!self.y
+            x:-self.x,
+            y:-self.y
         }
     }
 }
-impl Neg for Position6Cube{
-    type Output=Position6Cube;
+impl<C: Coord> Neg for Position6Cube<C>{
+    type Output=Position6Cube<C>;
 
     fn neg(self) -> Self::Output {
         Position6Cube{
-            x:!self.x,
-            y:!self.y,
-            z:!self.z
+            x:-self.x,
+            y:-self.y,
+            z:-self.z
         }
     }
 }
 
-impl From<(i16,i16)> for Position4{
-    fn from(f: (i16,i16)) -> Self {
-        Position4{x:f.0,y:f.1}
-    }
-}
-impl From<(i16,i16)> for Position6Axial{
-    fn from(f: (i16,i16)) -> Self {
-        Position6Axial{x:f.0,y:f.1}
-    }
-}
+impl<C: Coord> Neg for Position3<C>{
+    type Output=Position3<C>;
 
-impl From<(i16,i16,i16)> for Position6Cube{
-    fn from(f: (i16,i16,i16)) -> Self {
-        Position6Cube{x:f.0,y:f.1,z:f.2}
+    fn neg(self) -> Self::Output {
+        Position3{
+            x:-self.x,
+            y:-self.y,
+            up:self.up
+        }
     }
 }
 
-impl Digestable for Position4{
-    fn update_le<D:sha2::Digest>(&self,digest:&mut D) {
-        self.x.update_le(digest);
-        self.y.update_le(digest);
-    }
-
-    fn update_be<D:sha2::Digest>(&self,digest:&mut D) {
-        self.x.update_be(digest);
-        self.y.update_be(digest)
+impl<C: Coord> From<(C,C)> for Position4<C>{
+    fn from(f: (C,C)) -> Self {
+        Position4{x:f.0,y:f.1}
     }
 }
-impl Digestable for Position6Axial{
-    fn update_le<D:sha2::Digest>(&self,digest:&mut D) {
-        self.x.update_le(digest);
-        self.y.update_le(digest)
+impl<C: Coord> From<(C,C)> for Position6Axial<C>{
+    fn from(f: (C,C)) -> Self {
+        Position6Axial{x:f.0,y:f.1}
     }
+}
 
-    fn update_be<D:sha2::Digest>(&self,digest:&mut D) {
-        self.x.update_be(digest);
-        self.y.update_be(digest)
+impl<C: Coord> From<(C,C,C)> for Position6Cube<C>{
+    fn from(f: (C,C,C)) -> Self {
+        Position6Cube{x:f.0,y:f.1,z:f.2}
     }
 }
-impl Digestable for Position6Cube{
-    fn update_le<D:sha2::Digest>(&self,digest:&mut D) {
-        self.x.update_le(digest);
-        self.y.update_le(digest)
-    }
 
-    fn update_be<D:sha2::Digest>(&self,digest:&mut D) {
-        self.x.update_be(digest);
-        self.y.update_be(digest)
+impl<C: Coord> From<(C,C,bool)> for Position3<C>{
+    fn from(f: (C,C,bool)) -> Self {
+        Position3{x:f.0,y:f.1,up:f.2}
     }
 }
@@ -0,0 +1,354 @@
+use crate::area::{cube_of, to_hex};
+use crate::{
+    Adjacent, Coord, Edge, HexArea, Metric, Natural, Planar, Position, Position4, Position6, Side,
+    Terrain,
+};
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_set::{IntoIter, Iter};
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+/// A set of tiles, like a room, a selection, a spell area or the fog over a map.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct Region<P: Eq + Hash> {
+    tiles: HashSet<P>,
+}
+
+impl<P: Eq + Hash> Default for Region<P> {
+    fn default() -> Self {
+        Region {
+            tiles: HashSet::new(),
+        }
+    }
+}
+
+impl<P: Eq + Hash> Region<P> {
+    pub fn new() -> Self {
+        Region::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn contains(&self, tile: &P) -> bool {
+        self.tiles.contains(tile)
+    }
+
+    /// `false` if the tile was already part of the region.
+    pub fn insert(&mut self, tile: P) -> bool {
+        self.tiles.insert(tile)
+    }
+
+    /// `false` if the tile was not part of the region.
+    pub fn remove(&mut self, tile: &P) -> bool {
+        self.tiles.remove(tile)
+    }
+
+    pub fn iter(&self) -> Iter<'_, P> {
+        self.tiles.iter()
+    }
+
+    pub fn is_subset(&self, other: &Region<P>) -> bool {
+        self.tiles.is_subset(&other.tiles)
+    }
+}
+
+impl<P: Eq + Hash + Clone> Region<P> {
+    pub fn union(&self, other: &Region<P>) -> Region<P> {
+        self.tiles.union(&other.tiles).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &Region<P>) -> Region<P> {
+        self.tiles.intersection(&other.tiles).cloned().collect()
+    }
+
+    pub fn difference(&self, other: &Region<P>) -> Region<P> {
+        self.tiles.difference(&other.tiles).cloned().collect()
+    }
+
+    /// The tiles in exactly one of the regions.
+    pub fn xor(&self, other: &Region<P>) -> Region<P> {
+        self.tiles
+            .symmetric_difference(&other.tiles)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Tiles reached from `start` by moves `next` allows, at most `steps` of them.
+fn spread<P: Eq + Hash + Clone>(
+    start: &P,
+    steps: u32,
+    mut next: impl FnMut(&P) -> Vec<P>,
+) -> Region<P> {
+    let mut region = Region::new();
+    region.insert(start.clone());
+    let mut todo = VecDeque::from(vec![(start.clone(), 0)]);
+    while let Some((tile, depth)) = todo.pop_front() {
+        if depth == steps {
+            continue;
+        }
+        for n in next(&tile) {
+            if region.insert(n.clone()) {
+                todo.push_back((n, depth + 1));
+            }
+        }
+    }
+    region
+}
+
+impl<P: Position<P> + Eq + Hash + Clone> Region<P> {
+    /// Tiles whose centers are at most `radius` away from the center of `center`.
+    pub fn circle(center: &P, radius: u32) -> Region<P> {
+        let radius = f64::from(radius);
+        // the tiles of a disk are connected, so they can be found from the center
+        spread(center, u32::MAX, |tile| {
+            tile.checked_neighbours()
+                .into_iter()
+                .filter(|n| center.line_length(n) <= radius)
+                .collect()
+        })
+    }
+
+    /// The tiles with a neighbour outside the region.
+    pub fn boundary(&self) -> Region<P> {
+        self.tiles
+            .iter()
+            .filter(|tile| tile.checked_neighbours().iter().any(|n| !self.contains(n)))
+            .cloned()
+            .collect()
+    }
+
+    /// The parts of the region connected by `PositionHelper::neighbours`.
+    pub fn components(&self) -> Vec<Region<P>> {
+        self.components_with(&Natural)
+    }
+
+    /// The parts of the region connected by the moves of `metric`.
+    pub fn components_with<M: Metric<P> + ?Sized>(&self, metric: &M) -> Vec<Region<P>> {
+        let mut left = self.clone();
+        let mut components = Vec::new();
+        while let Some(start) = left.iter().next().cloned() {
+            let component = spread(&start, u32::MAX, |tile| {
+                metric
+                    .neighbours(tile)
+                    .into_iter()
+                    .filter(|n| left.contains(n))
+                    .collect()
+            });
+            for tile in component.iter() {
+                left.remove(tile);
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// The tiles `terrain` lets a walker reach from `start` in at most `steps` moves, whatever
+    /// they cost.
+    pub fn flood_fill<T: Terrain<P> + ?Sized>(start: &P, terrain: &T, steps: u32) -> Region<P> {
+        Region::flood_fill_with(start, terrain, steps, &Natural)
+    }
+
+    /// `flood_fill` with the moves of `metric`.
+    pub fn flood_fill_with<T, M>(start: &P, terrain: &T, steps: u32, metric: &M) -> Region<P>
+    where
+        T: Terrain<P> + ?Sized,
+        M: Metric<P> + ?Sized,
+    {
+        spread(start, steps, |tile| {
+            metric
+                .neighbours(tile)
+                .into_iter()
+                .filter(|n| terrain.cost(tile, n).is_some())
+                .collect()
+        })
+    }
+}
+
+impl<P: Eq + Hash + Clone> Region<P> {
+    /// The edges between the region and the tiles outside it, where the walls of a room go.
+    pub fn boundary_edges<D: Side>(&self) -> Vec<Edge<P, D>>
+    where
+        P: Adjacent<D>,
+    {
+        let mut edges = Vec::new();
+        for tile in self.tiles.iter() {
+            for side in D::iter() {
+                match tile.checked_neighbour(side) {
+                    Some(n) if !self.contains(&n) => edges.push(Edge::new(tile.clone(), side)),
+                    _ => {}
+                }
+            }
+        }
+        edges
+    }
+}
+
+impl<C: Coord> Region<Position4<C>> {
+    /// The tiles between two corners, both included.
+    pub fn rectangle(a: &Position4<C>, b: &Position4<C>) -> Region<Position4<C>> {
+        let (x0, x1) = (a.x.min(b.x), a.x.max(b.x));
+        let (y0, y1) = (a.y.min(b.y), a.y.max(b.y));
+        let mut region = Region::new();
+        for x in x0.widen()..=x1.widen() {
+            for y in y0.widen()..=y1.widen() {
+                region.insert(Position4 {
+                    x: C::narrow(x).expect("between the corners"),
+                    y: C::narrow(y).expect("between the corners"),
+                });
+            }
+        }
+        region
+    }
+}
+
+impl<P: Position6 + Eq + Hash> Region<P> {
+    /// The hexes between two corners along the x and y axes, both included. Hexes outside the
+    /// coordinate range are skipped.
+    pub fn parallelogram(a: &P, b: &P) -> Region<P> {
+        let ((ax, ay, _), (bx, by, _)) = (cube_of(a), cube_of(b));
+        let mut region = Region::new();
+        for x in ax.min(bx)..=ax.max(bx) {
+            for y in ay.min(by)..=ay.max(by) {
+                region.tiles.extend(to_hex(x, y, -x - y));
+            }
+        }
+        region
+    }
+
+    /// The hexes at most `radius` away.
+    pub fn hexagon(center: &P, radius: u32) -> Region<P> {
+        HexArea::new(center, radius).collect()
+    }
+}
+
+/// `(p - a) x (b - a)`, zero on the line through `a` and `b`.
+fn cross(a: Vector2<f64>, b: Vector2<f64>, p: Vector2<f64>) -> f64 {
+    (p.x - a.x) * (b.y - a.y) - (p.y - a.y) * (b.x - a.x)
+}
+
+fn is_on_segment(a: Vector2<f64>, b: Vector2<f64>, p: Vector2<f64>) -> bool {
+    cross(a, b, p) == 0.0
+        && p.x >= a.x.min(b.x)
+        && p.x <= a.x.max(b.x)
+        && p.y >= a.y.min(b.y)
+        && p.y <= a.y.max(b.y)
+}
+
+/// Even-odd rule, points on the outline are inside.
+fn is_in_polygon(corners: &[Vector2<f64>], p: Vector2<f64>) -> bool {
+    let mut inside = false;
+    for (i, &a) in corners.iter().enumerate() {
+        let b = corners[(i + 1) % corners.len()];
+        if is_on_segment(a, b, p) {
+            return true;
+        }
+        if (a.y > p.y) != (b.y > p.y) && (cross(a, b, p) < 0.0) == (b.y > a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+impl<P: Planar + Eq + Hash> Region<P> {
+    /// The tiles whose centers lie inside or on the polygon through the centers of `corners`.
+    pub fn polygon(corners: &[P]) -> Region<P> {
+        let corners: Vec<_> = corners.iter().map(Planar::to_plane).collect();
+        let mut region = Region::new();
+        if corners.is_empty() {
+            return region;
+        }
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for c in corners.iter() {
+            min = min.inf(c);
+            max = max.sup(c);
+        }
+        let mut y = min.y;
+        while y <= max.y {
+            let mut x = min.x;
+            while x <= max.x {
+                let p = Vector2::new(x, y);
+                if is_in_polygon(&corners, p) {
+                    region.insert(P::from_plane(p));
+                }
+                x += 1.0;
+            }
+            y += 1.0;
+        }
+        region
+    }
+}
+
+impl<P: Eq + Hash> FromIterator<P> for Region<P> {
+    fn from_iter<I: IntoIterator<Item = P>>(iter: I) -> Self {
+        Region {
+            tiles: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<P: Eq + Hash> Extend<P> for Region<P> {
+    fn extend<I: IntoIterator<Item = P>>(&mut self, iter: I) {
+        self.tiles.extend(iter)
+    }
+}
+
+impl<P: Eq + Hash> IntoIterator for Region<P> {
+    type Item = P;
+    type IntoIter = IntoIter<P>;
+
+    fn into_iter(self) -> IntoIter<P> {
+        self.tiles.into_iter()
+    }
+}
+
+impl<'a, P: Eq + Hash> IntoIterator for &'a Region<P> {
+    type Item = &'a P;
+    type IntoIter = Iter<'a, P>;
+
+    fn into_iter(self) -> Iter<'a, P> {
+        self.tiles.iter()
+    }
+}
+
+impl<P: Eq + Hash + Clone> BitOr for &Region<P> {
+    type Output = Region<P>;
+
+    fn bitor(self, rhs: &Region<P>) -> Region<P> {
+        self.union(rhs)
+    }
+}
+
+impl<P: Eq + Hash + Clone> BitAnd for &Region<P> {
+    type Output = Region<P>;
+
+    fn bitand(self, rhs: &Region<P>) -> Region<P> {
+        self.intersection(rhs)
+    }
+}
+
+impl<P: Eq + Hash + Clone> Sub for &Region<P> {
+    type Output = Region<P>;
+
+    fn sub(self, rhs: &Region<P>) -> Region<P> {
+        self.difference(rhs)
+    }
+}
+
+impl<P: Eq + Hash + Clone> BitXor for &Region<P> {
+    type Output = Region<P>;
+
+    fn bitxor(self, rhs: &Region<P>) -> Region<P> {
+        self.xor(rhs)
+    }
+}
@@ -1,9 +1,8 @@
-use std::{collections::HashMap, error::Error, fmt::Display};
+use std::{collections::HashMap, error::Error, fmt::Display, pin::Pin};
 
-use crate::{ChangeMessage, Position};
+use crate::{ChangeId, ChangeMessage, Position};
 use futures::Future;
 use petgraph::graph::{DiGraph, NodeIndex};
-use std::borrow::Borrow;
 
 #[derive(Debug, Clone)]
 pub struct NoSuchBranch {
@@ -34,10 +33,10 @@ impl Error for NoSuchBranch {
 }
 #[derive(Debug, Clone)]
 pub struct MissingParent {
-    parent: [u8; 32],
+    parent: ChangeId,
 }
-impl From<[u8; 32]> for MissingParent {
-    fn from(p: [u8; 32]) -> Self {
+impl From<ChangeId> for MissingParent {
+    fn from(p: ChangeId) -> Self {
         MissingParent { parent: p }
     }
 }
@@ -58,11 +57,12 @@ impl Error for MissingParent {
 
 pub trait StorageBackend<P: Position<P>> {
     type EmptyFuture: Future<Output = Result<(), Box<dyn Error>>>+Unpin;
-    fn add_change(self, m: &ChangeMessage<P>) -> Self::EmptyFuture;
-    fn set_branch(self, b: &(String, String), n: [u8; 32]) -> Self::EmptyFuture;
+    fn add_change(&mut self, m: &ChangeMessage<P>) -> Self::EmptyFuture;
+    fn set_branch(&mut self, b: &(String, String), n: ChangeId) -> Self::EmptyFuture;
 }
 
-enum StoreError {
+#[derive(Debug)]
+pub enum StoreError {
     Branch(NoSuchBranch),
     Parent(MissingParent),
 }
@@ -76,50 +76,45 @@ impl From<StoreError> for Box<dyn Error> {
     }
 }
 
+type ChangeHandler<P> = Box<
+    dyn Fn(&ChangeMessage<P>) -> Option<Box<dyn Future<Output = Result<(), Box<dyn Error>>>+Unpin>>,
+>;
+
+type StoreFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Box<dyn Error>>> + 'a>>;
+
 pub struct Storage<P: Position<P>, S: StorageBackend<P>> {
-    store: DiGraph<[u8; 32], (), u32>,
-    node_map: HashMap<[u8; 32], NodeIndex<u32>>,
-    branches: HashMap<(String, String), [u8; 32]>,
-    change_handlers: Vec<(
-        u32,
-        Box<
-            dyn Fn(
-                &ChangeMessage<P>,
-            ) -> Option<Box<dyn Future<Output = Result<(), Box<dyn Error>>>+Unpin>>,
-        >,
-    )>,
+    store: DiGraph<ChangeId, (), u32>,
+    node_map: HashMap<ChangeId, NodeIndex<u32>>,
+    branches: HashMap<(String, String), ChangeId>,
+    change_handlers: Vec<(u32, ChangeHandler<P>)>,
     backend: S,
 }
 
 impl<P: Position<P>, S: StorageBackend<P>> Storage<P, S> {
-    fn add_change_to_branch(
-        &mut self,
-        m: &ChangeMessage<P>,
-        b: &(String, String),
-    ) -> Result<impl Future<Output = Result<(), Box<dyn Error>>>+Unpin, StoreError> {
+    /// Adds `m` to the history and moves branch `b` to it. The future hands both to the backend
+    /// and then runs the change handlers.
+    pub fn add_change_to_branch<'a>(
+        &'a mut self,
+        m: &'a ChangeMessage<P>,
+        b: &'a (String, String),
+    ) -> Result<StoreFuture<'a>, StoreError> {
         if self.branches.contains_key(b) {
             for p in m.change.parents.iter() {
                 if !self.node_map.contains_key(p) {
-                    return Err(StoreError::Parent(MissingParent::from(p.clone())));
+                    return Err(StoreError::Parent(MissingParent::from(*p)));
                 }
             }
-            return async {
+            Ok(Box::pin(async move {
                 let change_p = self.backend.add_change(m);
-                let node = self.store.add_node(m.sha256.clone());
-                m.change.parents.iter().for_each(|p| {
-                    self.store.add_edge(
-                        self.node_map.get(p).expect("just tested!").clone(),
-                        node,
-                        (),
-                    );
-                });
-                self.node_map.insert(m.sha256.clone(), node);
+                let node = self.store.add_node(m.id);
+                for p in m.change.parents.iter() {
+                    let parent = *self.node_map.get(p).expect("just tested!");
+                    self.store.add_edge(parent, node, ());
+                }
+                self.node_map.insert(m.id, node);
                 change_p.await?;
-                let branch_p = self.backend.set_branch(b, m.sha256);
-                self.branches
-                    .get_mut(b)
-                    .expect("just tested")
-                    .copy_from_slice(m.sha256.borrow());
+                let branch_p = self.backend.set_branch(b, m.id);
+                *self.branches.get_mut(b).expect("just tested") = m.id;
                 branch_p.await?;
                 for p in self.change_handlers.iter().map(|h| h.1(m)) {
                     match p {
@@ -130,9 +125,10 @@ impl<P: Position<P>, S: StorageBackend<P>> Storage<P, S> {
                         },
                     };
                 }
-            }
+                Ok(())
+            }))
         } else {
-            return Err(StoreError::Branch(NoSuchBranch::from(b.clone())));
+            Err(StoreError::Branch(NoSuchBranch::from(b.clone())))
         }
     }
 }
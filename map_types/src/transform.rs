@@ -0,0 +1,138 @@
+use crate::{Coord, Layered, Position4, Position6, Position6Axial, Position6Cube};
+
+/// The grid axes of a square grid.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Axis4 {
+    X,
+    Y,
+}
+
+/// The three axes of a hex grid, named after the cube coordinate that stays constant along them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Axis6 {
+    X,
+    Y,
+    Z,
+}
+
+/// Rotations and reflections that map the grid onto itself.
+///
+/// Rotations are clockwise with y pointing down, as on screen. Negative steps rotate
+/// counterclockwise.
+pub trait Transform: Sized {
+    type Axis;
+    /// Steps of a full turn, 4 on square and 6 on hex grids.
+    const STEPS: i32;
+
+    fn rotate(&self, pivot: &Self, steps: i32) -> Self;
+    /// Mirrors across the line through `pivot` parallel to `axis`.
+    fn reflect(&self, pivot: &Self, axis: Self::Axis) -> Self;
+}
+
+impl<C: Coord> Transform for Position4<C> {
+    type Axis = Axis4;
+    const STEPS: i32 = 4;
+
+    fn rotate(&self, pivot: &Self, steps: i32) -> Self {
+        let d = *self - *pivot;
+        let d = match steps.rem_euclid(Self::STEPS) {
+            0 => d,
+            1 => Position4 { x: -d.y, y: d.x },
+            2 => -d,
+            _ => Position4 { x: d.y, y: -d.x },
+        };
+        *pivot + d
+    }
+
+    fn reflect(&self, pivot: &Self, axis: Axis4) -> Self {
+        let d = *self - *pivot;
+        let d = match axis {
+            Axis4::X => Position4 { x: d.x, y: -d.y },
+            Axis4::Y => Position4 { x: -d.x, y: d.y },
+        };
+        *pivot + d
+    }
+}
+
+impl<C: Coord> Transform for Position6Cube<C> {
+    type Axis = Axis6;
+    const STEPS: i32 = 6;
+
+    fn rotate(&self, pivot: &Self, steps: i32) -> Self {
+        let d = *self - *pivot;
+        let d = match steps.rem_euclid(Self::STEPS) {
+            0 => d,
+            1 => Position6Cube {
+                x: -d.y,
+                y: -d.z,
+                z: -d.x,
+            },
+            2 => Position6Cube {
+                x: d.z,
+                y: d.x,
+                z: d.y,
+            },
+            3 => -d,
+            4 => Position6Cube {
+                x: d.y,
+                y: d.z,
+                z: d.x,
+            },
+            _ => Position6Cube {
+                x: -d.z,
+                y: -d.x,
+                z: -d.y,
+            },
+        };
+        *pivot + d
+    }
+
+    fn reflect(&self, pivot: &Self, axis: Axis6) -> Self {
+        let d = *self - *pivot;
+        let d = match axis {
+            Axis6::X => Position6Cube {
+                x: -d.x,
+                y: -d.z,
+                z: -d.y,
+            },
+            Axis6::Y => Position6Cube {
+                x: -d.z,
+                y: -d.y,
+                z: -d.x,
+            },
+            Axis6::Z => Position6Cube {
+                x: -d.y,
+                y: -d.x,
+                z: -d.z,
+            },
+        };
+        *pivot + d
+    }
+}
+
+impl<C: Coord> Transform for Position6Axial<C> {
+    type Axis = Axis6;
+    const STEPS: i32 = 6;
+
+    fn rotate(&self, pivot: &Self, steps: i32) -> Self {
+        self.to_cube().rotate(&pivot.to_cube(), steps).to_axial()
+    }
+
+    fn reflect(&self, pivot: &Self, axis: Axis6) -> Self {
+        self.to_cube().reflect(&pivot.to_cube(), axis).to_axial()
+    }
+}
+
+/// Turns the level around the pivot's column, the level stays.
+impl<P: Transform> Transform for Layered<P> {
+    type Axis = P::Axis;
+    const STEPS: i32 = P::STEPS;
+
+    fn rotate(&self, pivot: &Self, steps: i32) -> Self {
+        Layered::new(self.position.rotate(&pivot.position, steps), self.level)
+    }
+
+    fn reflect(&self, pivot: &Self, axis: P::Axis) -> Self {
+        Layered::new(self.position.reflect(&pivot.position, axis), self.level)
+    }
+}
@@ -1,29 +1,18 @@
+use std::{error::Error, fmt::Display};
 use serde::{Deserialize,Serialize};
-use sha2::{Digest, Sha256};
-use structured_digest::Digestable;
-use crate::Position;
+use sha2::{Sha256, Sha512Trunc256};
+use structured_digest::{merkle, ByteSink, Decode, DecodeError, Digestable, Encoding, MerkleProof, Version};
+use crate::{ChangeId, Direction, HashAlgorithm, IdDigest, Position};
 
-#[derive(Serialize,Deserialize,Debug,Clone)]
+#[derive(Serialize,Deserialize,Debug,Clone,Digestable,Decode)]
+#[digest(tag = "map_types::ElementChange")]
 pub struct ElementChange<P:Position<P>> {
     pub id:u64,
     pub variant:Option<u16>,
     pub position:Option<P>,
 }
-impl<P:Position<P>> Digestable for ElementChange<P>{
-    fn update_le<D:sha2::Digest>(&self,digest:&mut D) {
-        self.id.update_le(digest);
-        self.variant.update_le(digest);
-        self.position.update_le(digest)
-    }
-
-    fn update_be<D:sha2::Digest>(&self,digest:&mut D) {
-        self.id.update_be(digest);
-        self.variant.update_be(digest);
-        self.position.update_be(digest)
-    }
-}
-
-#[derive(Serialize,Deserialize,Debug, Clone)]
+#[derive(Serialize,Deserialize,Debug, Clone,Digestable,Decode)]
+#[digest(tag = "map_types::EntityChange")]
 pub struct EntityChange<P:Position<P>> {
     pub id: u64,
     pub ent_type: Option<String>,
@@ -33,60 +22,182 @@ pub struct EntityChange<P:Position<P>> {
     pub elements: Option<Option<Vec<ElementChange<P>>>>,
 }
 
-impl<P:Position<P>> Digestable for EntityChange<P>{
-    fn update_le<D:sha2::Digest>(&self,digest:&mut D) {
-        self.id.update_le(digest);
-        self.ent_type.update_le(digest);
-        self.variant.update_le(digest);
-        self.orientation.update_le(digest);
-        self.base_position.update_le(digest);
-        self.elements.update_le(digest)
+impl<P:Position<P>> EntityChange<P>{
+    /// The new `orientation` as a direction of the grid, `None` if unchanged or out of range.
+    pub fn direction<D:Direction>(&self) -> Option<D> {
+        D::from_index(self.orientation?.into())
     }
 
-    fn update_be<D:sha2::Digest>(&self,digest:&mut D) {
-        self.id.update_be(digest);
-        self.ent_type.update_be(digest);
-        self.variant.update_be(digest);
-        self.orientation.update_be(digest);
-        self.base_position.update_be(digest);
-        self.elements.update_be(digest)
+    pub fn set_direction<D:Direction>(&mut self,direction:D) {
+        self.orientation=Some(direction.index() as u8);
+    }
+
+    /// Checks a proof from `ChangeMessage::prove_entity` against the id of a message hashed with `scheme`.
+    pub fn verify_inclusion<D:IdDigest>(&self,id:&ChangeId,scheme:HashScheme,proof:&MerkleProof<D>) -> bool {
+        id.algorithm==D::ALGORITHM && proof.verify(self.merkle_root::<D>(Encoding::le(scheme.version())),&id.hash)
     }
 }
 
-#[derive(Serialize,Deserialize,Debug,Clone)]
+#[derive(Serialize,Deserialize,Debug,Clone,Digestable,Decode)]
+#[digest(tag = "map_types::Change")]
 pub struct Change<P:Position<P>>{
-    pub parents: Vec<[u8;32]>,
+    #[digest(with = parents)]
+    pub parents: Vec<ChangeId>,
     pub changes:Vec<EntityChange<P>>,
 }
 
-impl <P:Position<P>> Digestable for Change<P>{
-    fn update_le<D:sha2::Digest>(&self,digest:&mut D) {
-        let mut d= D::new();
-        self.parents.update_le(&mut d);
-        digest.update(d.finalize());
-        self.changes.update_le(digest);
+/// `Version::V0` hashed the parents into an inner digest. Later versions encode them in place,
+/// so the hashed bytes can be decoded again.
+mod parents{
+    use sha2::Sha256;
+    use structured_digest::{Decode, DecodeError, Digestable, Encoding, Nested, Reader, Sink, Version};
+    use crate::ChangeId;
+
+    pub fn digest<S:Sink+?Sized>(parents:&[ChangeId],sink:&mut S,encoding:Encoding){
+        if encoding.version==Version::V0 {
+            Nested::<Sha256>::digest(parents,sink,encoding)
+        }else{
+            parents.digest(sink,encoding)
+        }
+    }
+
+    pub fn decode(reader:&mut Reader<'_>)->Result<Vec<ChangeId>,DecodeError>{
+        Vec::decode(reader)
     }
+}
+
+/// How `ChangeMessage::id` was computed. Messages without a scheme predate versioning.
+/// `V0` encodes parents without their algorithm, so they have to be SHA-256 ids and
+/// `ChangeMessage::new` rejects others.
+#[derive(Serialize,Deserialize,Debug,Copy,Clone,Eq,PartialEq,Ord,PartialOrd,Hash,Default)]
+pub enum HashScheme{
+    /// `Change::update_le` with `Version::V0`, the unframed encoding.
+    #[default]
+    V0,
+    /// `Change::update_le` with `Version::V2`, so `id` is the hash of `canonical_bytes`.
+    V2,
+    /// `Change::merkle_root` over `Version::V2` leaves, so single entity changes can be proven.
+    MerkleV2,
+}
+impl HashScheme{
+    pub const LATEST:HashScheme=HashScheme::V2;
 
-    fn update_be<D:sha2::Digest>(&self,digest:&mut D) {
-        let mut d=D::new();
-        self.parents.update_be(&mut d);
-        digest.update(d.finalize());
-        self.changes.update_be(digest);
+    pub fn version(self)->Version{
+        match self {
+            HashScheme::V0=>Version::V0,
+            HashScheme::V2|HashScheme::MerkleV2=>Version::V2,
+        }
     }
 }
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
 pub struct ChangeMessage<P:Position<P>>{
     pub change:Change<P>,
-    pub sha256:[u8;32]
+    #[serde(alias = "sha256")]
+    pub id:ChangeId,
+    #[serde(default)]
+    pub scheme:HashScheme,
+}
+impl<P:Position<P>> ChangeMessage<P>{
+    pub fn new(c: Change<P>,scheme:HashScheme) -> Result<Self,MessageError> {
+        ChangeMessage::with_algorithm(c,scheme,HashAlgorithm::Sha256)
+    }
+
+    /// Fails if `scheme` can't encode the algorithm of a parent id.
+    pub fn with_algorithm(c: Change<P>,scheme:HashScheme,algorithm:HashAlgorithm) -> Result<Self,MessageError> {
+        if scheme==HashScheme::V0 {
+            if let Some(p)=c.parents.iter().find(|p|p.algorithm!=HashAlgorithm::Sha256) {
+                return Err(MessageError::UnsupportedParent(*p));
+            }
+        }
+        Ok(ChangeMessage{
+            id:Self::hash(&c,scheme,algorithm),
+            change:c,
+            scheme
+        })
+    }
+
+    fn hash(c: &Change<P>,scheme:HashScheme,algorithm:HashAlgorithm) -> ChangeId {
+        match algorithm {
+            HashAlgorithm::Sha256=>Self::hash_with::<Sha256>(c,scheme),
+            HashAlgorithm::Sha512Trunc256=>Self::hash_with::<Sha512Trunc256>(c,scheme),
+            HashAlgorithm::Blake2s256=>Self::hash_with::<blake2::Blake2s>(c,scheme),
+            HashAlgorithm::Blake3=>Self::hash_with::<blake3::Hasher>(c,scheme),
+        }
+    }
+
+    fn hash_with<D:IdDigest>(c: &Change<P>,scheme:HashScheme) -> ChangeId {
+        if scheme==HashScheme::MerkleV2 {
+            ChangeId::new::<D>(c.merkle_root::<D>(Encoding::le(scheme.version())))
+        }else{
+            let mut hasher=D::new();
+            c.update_le(&mut hasher,scheme.version());
+            ChangeId::new::<D>(hasher.finalize())
+        }
+    }
+
+    /// The encoded change. `id` is the hash of exactly these bytes, except for
+    /// `HashScheme::MerkleV2`, which hashes the fields separately.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes=ByteSink::default();
+        self.change.update_le(&mut bytes,self.scheme.version());
+        bytes.0
+    }
+
+    /// Recomputes the hash of `change` with the recorded scheme and algorithm.
+    pub fn verify(&self) -> bool {
+        Self::hash(&self.change,self.scheme,self.id.algorithm)==self.id
+    }
+
+    /// Proof that `change.changes[index]` is part of this message, see `EntityChange::verify_inclusion`.
+    /// Only messages hashed with `HashScheme::MerkleV2` and with the algorithm of `D` have one.
+    pub fn prove_entity<D:IdDigest>(&self,index:usize) -> Option<MerkleProof<D>> {
+        if self.scheme!=HashScheme::MerkleV2 || self.id.algorithm!=D::ALGORITHM {
+            return None;
+        }
+        let encoding=Encoding::le(self.scheme.version());
+        let entity=merkle::prove_child(&self.change.changes,index,encoding)?;
+        let changes=merkle::prove_field(&self.change,"changes",encoding)?;
+        Some(entity.then(changes))
+    }
+}
+impl<P:Position<P>+Decode> ChangeMessage<P>{
+    /// Decodes bytes produced by `canonical_bytes` and hashes the change with `scheme` and `algorithm`.
+    pub fn from_canonical_bytes(bytes:&[u8],scheme:HashScheme,algorithm:HashAlgorithm) -> Result<Self,MessageError> {
+        let change=structured_digest::from_bytes(bytes,Encoding::le(scheme.version()))?;
+        ChangeMessage::with_algorithm(change,scheme,algorithm)
+    }
 }
 impl<P:Position<P>> From<Change<P>> for ChangeMessage<P>{
     fn from(c: Change<P>) -> Self {
-        let mut hasher=Sha256::new();
-        c.update_le(&mut hasher);
-        ChangeMessage{
-            change:c,
-            sha256:hasher.finalize().into()
+        ChangeMessage::new(c,HashScheme::LATEST).expect("the latest scheme encodes every algorithm")
+    }
+}
+
+#[derive(Debug,Clone,Eq,PartialEq)]
+pub enum MessageError{
+    /// `HashScheme::V0` encodes parents as bare SHA-256 hashes.
+    UnsupportedParent(ChangeId),
+    Decode(DecodeError),
+}
+impl From<DecodeError> for MessageError{
+    fn from(e: DecodeError) -> Self {
+        MessageError::Decode(e)
+    }
+}
+impl Display for MessageError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessageError::UnsupportedParent(p)=>write!(f,"Parent {:?} can not be encoded by the hash scheme",p),
+            MessageError::Decode(e)=>write!(f,"{}",e),
+        }
+    }
+}
+impl Error for MessageError{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MessageError::UnsupportedParent(_)=>None,
+            MessageError::Decode(e)=>Some(e),
         }
     }
 }
@@ -0,0 +1,115 @@
+use crate::{Line, Obstacles, Position};
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// What blocks sight on a map.
+pub trait Sight<P> {
+    /// Opaque tiles are visible themselves but hide what is behind them.
+    fn is_opaque(&self, tile: &P) -> bool;
+    /// Thin walls on the edge between two neighbours.
+    fn is_wall(&self, a: &P, b: &P) -> bool;
+}
+
+impl<P: Eq + Hash + Clone> Sight<P> for Obstacles<P> {
+    fn is_opaque(&self, tile: &P) -> bool {
+        self.is_blocked(tile)
+    }
+
+    fn is_wall(&self, a: &P, b: &P) -> bool {
+        Obstacles::is_wall(self, a, b)
+    }
+}
+
+/// How the lines from the viewer and back to it combine. Lines on a grid are not symmetric,
+/// a tile may be reachable by one but not the other.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Vision {
+    /// Both lines have to be clear. Nothing is seen that does not see the viewer back.
+    Symmetric,
+    /// One clear line is enough, reveals more around corners.
+    Permissive,
+}
+
+fn is_step_clear<P, S>(from: &P, to: &P, sight: &S) -> bool
+where
+    P: Position<P> + Eq + Clone,
+    S: Sight<P> + ?Sized,
+{
+    let neighbours = from.checked_neighbours();
+    if neighbours.contains(to) {
+        return !sight.is_wall(from, to);
+    }
+    // a diagonal step passes a corner, going around either side of it has to be possible
+    neighbours.iter().any(|side| {
+        side.checked_neighbours().contains(to)
+            && !sight.is_opaque(side)
+            && !sight.is_wall(from, side)
+            && !sight.is_wall(side, to)
+    })
+}
+
+fn is_line_clear<P, S>(from: &P, to: &P, sight: &S) -> bool
+where
+    P: Position<P> + Line + Eq + Clone,
+    S: Sight<P> + ?Sized,
+{
+    let mut line = from.line_to(to);
+    let mut previous = match line.next() {
+        Some(p) => p,
+        None => return true,
+    };
+    for tile in line {
+        if !is_step_clear(&previous, &tile, sight) {
+            return false;
+        }
+        if tile == *to {
+            return true;
+        }
+        if sight.is_opaque(&tile) {
+            return false;
+        }
+        previous = tile;
+    }
+    true
+}
+
+/// Whether `to` can be seen from `from`.
+pub fn is_visible<P, S>(from: &P, to: &P, sight: &S, vision: Vision) -> bool
+where
+    P: Position<P> + Line + Eq + Clone,
+    S: Sight<P> + ?Sized,
+{
+    match vision {
+        Vision::Symmetric => is_line_clear(from, to, sight) && is_line_clear(to, from, sight),
+        Vision::Permissive => is_line_clear(from, to, sight) || is_line_clear(to, from, sight),
+    }
+}
+
+/// Largest radius `field_of_view` looks at. With an open field around the viewer this radius
+/// takes about 2 ms on a square and 5 ms on a hex grid, twice the radius eight times as long
+/// (`cargo bench --bench visibility`).
+pub const MAX_VIEW_RADIUS: u32 = 16;
+
+/// The tiles visible from `viewer` whose centers are at most `radius` away, by `line_length`.
+///
+/// This is not shadowcasting: every tile in range is checked with its own `is_visible` lines,
+/// so it takes O(r³) steps for a radius r. Larger radii are cut down to `MAX_VIEW_RADIUS`.
+pub fn field_of_view<P, S>(viewer: &P, radius: u32, sight: &S, vision: Vision) -> HashSet<P>
+where
+    P: Position<P> + Line + Eq + Hash + Clone,
+    S: Sight<P> + ?Sized,
+{
+    let radius = f64::from(radius.min(MAX_VIEW_RADIUS)) + 1e-9;
+    let mut area = HashSet::new();
+    let mut todo = vec![viewer.clone()];
+    area.insert(viewer.clone());
+    while let Some(tile) = todo.pop() {
+        for next in tile.checked_neighbours() {
+            if viewer.line_length(&next) <= radius && area.insert(next.clone()) {
+                todo.push(next);
+            }
+        }
+    }
+    area.retain(|tile| is_visible(viewer, tile, sight, vision));
+    area
+}
@@ -0,0 +1,110 @@
+use map_types::*;
+use proptest::prelude::*;
+
+fn turns<D: Direction>() -> Result<(), TestCaseError> {
+    for (i, &d) in D::ALL.iter().enumerate() {
+        prop_assert_eq!(d.index(), i);
+        prop_assert_eq!(D::from_index(i), Some(d));
+        prop_assert_eq!(d.rotate_cw().rotate_ccw(), d);
+        prop_assert_eq!(d.opposite().opposite(), d);
+        prop_assert_ne!(d.opposite(), d);
+        let half = (0..D::ALL.len() / 2).fold(d, |d, _| d.rotate_cw());
+        prop_assert_eq!(half, d.opposite());
+        let (x, y) = d.offset();
+        prop_assert_eq!(d.opposite().offset(), (-x, -y));
+    }
+    prop_assert!(D::from_index(D::ALL.len()).is_none());
+    Ok(())
+}
+
+fn steps<D: Direction, P: Adjacent<D> + PositionHelper + Eq + Clone + std::fmt::Debug>(
+    p: &P,
+) -> Result<(), TestCaseError> {
+    for d in D::iter() {
+        let n = p.neighbour(d);
+        prop_assert_eq!(p.checked_neighbour(d), Some(n.clone()));
+        prop_assert_eq!(p.direction_to(&n), Some(d));
+        prop_assert_eq!(n.direction_to(p), Some(d.opposite()));
+        prop_assert_eq!(&n.neighbour(d.opposite()), p);
+    }
+    prop_assert_eq!(p.direction_to(p), None);
+    Ok(())
+}
+
+#[test]
+fn directions_turn() {
+    turns::<Direction4>().unwrap();
+    turns::<Direction8>().unwrap();
+    turns::<Direction6>().unwrap();
+}
+
+proptest! {
+    #[test]
+    fn neighbours_follow_the_directions(x in -8000i16..8000, y in -8000i16..8000, level in any::<i16>()) {
+        let square = Position4 { x, y };
+        let hex = Position6Axial { x, y };
+        prop_assert_eq!(Direction4::iter().map(|d| square.neighbour(d)).collect::<Vec<_>>(), square.neighbours());
+        prop_assert_eq!(Direction6::iter().map(|d| hex.neighbour(d)).collect::<Vec<_>>(), hex.neighbours());
+        prop_assert_eq!(Direction8::iter().map(|d| square.neighbour(d)).collect::<Vec<_>>(), Metric4::Chebyshev.neighbours(&square));
+        steps::<Direction4, _>(&square)?;
+        steps::<Direction8, _>(&square)?;
+        steps::<Direction6, _>(&hex)?;
+        steps::<Direction6, _>(&hex.to_cube())?;
+        steps::<Direction6, _>(&Layered::new(hex, level))?;
+    }
+
+    #[test]
+    fn directions_lead_closer(a in (-100i16..100, -100i16..100), b in (-100i16..100, -100i16..100)) {
+        prop_assume!(a != b);
+        let (a4, b4) = (Position4::from(a), Position4::from(b));
+        let d: Direction4 = a4.direction_to(&b4).unwrap();
+        prop_assert_eq!(a4.neighbour(d).field_length(&b4) + 1, a4.field_length(&b4));
+        let d: Direction8 = a4.direction_to(&b4).unwrap();
+        prop_assert_eq!(Metric4::Chebyshev.distance(&a4.neighbour(d), &b4) + 1, Metric4::Chebyshev.distance(&a4, &b4));
+        let (a6, b6) = (Position6Axial::from(a), Position6Axial::from(b));
+        let d: Direction6 = a6.direction_to(&b6).unwrap();
+        prop_assert_eq!(a6.neighbour(d).field_length(&b6) + 1, a6.field_length(&b6));
+        prop_assert!(a6.neighbour(d).line_length(&b6) < a6.line_length(&b6));
+    }
+
+    #[test]
+    fn borders_wall_off_named_sides(x in -100i16..100, y in -100i16..100, side in 0usize..6) {
+        let side = Direction6::from_index(side).unwrap();
+        let hex = Position6Axial { x, y };
+        let mut borders = Borders6::default();
+        borders[side] = true;
+        let mut walls = Obstacles::new(1);
+        walls.block_borders(&hex, &borders);
+        for d in Direction6::iter() {
+            prop_assert_eq!(borders.is_wall(d), d == side);
+            prop_assert_eq!(walls.is_wall(&hex, &hex.neighbour(d)), d == side);
+        }
+    }
+}
+
+#[test]
+fn borders_keep_their_layout() {
+    let mut borders = Borders4::default();
+    borders[Direction4::North] = true;
+    assert_eq!(borders, Borders4(false, false, false, true));
+    assert_eq!(serde_json::to_string(&borders).unwrap(), "[false,false,false,true]");
+}
+
+#[test]
+fn orientations_are_indices() {
+    assert_eq!(serde_json::to_string(&Direction6::NorthWest).unwrap(), "4");
+    assert_eq!(serde_json::from_str::<Direction8>("7").unwrap(), Direction8::NorthEast);
+    assert!(serde_json::from_str::<Direction4>("4").is_err());
+    let mut change = EntityChange::<Position4> {
+        id: 1,
+        ent_type: None,
+        variant: None,
+        orientation: None,
+        base_position: None,
+        elements: None,
+    };
+    assert_eq!(change.direction::<Direction4>(), None);
+    change.set_direction(Direction4::West);
+    assert_eq!(change.orientation, Some(2));
+    assert_eq!(change.direction(), Some(Direction4::West));
+}
@@ -0,0 +1,91 @@
+use map_types::*;
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+fn corners<D: Side, P: Adjacent<D> + Position<P> + Eq + std::hash::Hash + Clone + std::fmt::Debug>(
+    tile: P,
+    tiles_per_corner: usize,
+) -> Result<(), TestCaseError> {
+    let edges = Edge::<P, D>::around(&tile);
+    prop_assert_eq!(edges.iter().collect::<HashSet<_>>().len(), D::ALL.len());
+    for (edge, side) in edges.iter().zip(D::iter()) {
+        let across = tile.neighbour(side);
+        prop_assert_eq!(edge, &Edge::new(across.clone(), side.opposite()));
+        let (a, b) = edge.tiles();
+        prop_assert_eq!(HashSet::from([a, b]), HashSet::from([tile.clone(), across.clone()]));
+        let (first, second) = edge.ends();
+        for end in [first, second] {
+            let tiles = end.tiles();
+            prop_assert!(tiles.contains(&tile) && tiles.contains(&across));
+            prop_assert!(end.edges().contains(edge));
+        }
+    }
+    let vertices = Vertex::<P, D>::around(&tile);
+    prop_assert_eq!(vertices.iter().collect::<HashSet<_>>().len(), D::ALL.len());
+    for (vertex, side) in vertices.iter().zip(D::iter()) {
+        let tiles = vertex.tiles();
+        prop_assert_eq!(tiles.len(), tiles_per_corner);
+        prop_assert_eq!(tiles.iter().collect::<HashSet<_>>().len(), tiles_per_corner);
+        prop_assert!(tiles.contains(&tile) && tiles.contains(&tile.neighbour(side)) && tiles.contains(&tile.neighbour(side.rotate_cw())));
+        for t in tiles.iter() {
+            prop_assert!(Vertex::around(t).contains(vertex));
+        }
+        let meeting = vertex.edges();
+        prop_assert_eq!(meeting.len(), tiles_per_corner);
+        for e in meeting.iter() {
+            let (a, b) = e.tiles();
+            prop_assert!(tiles.contains(&a) && tiles.contains(&b));
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn edges_and_corners_are_shared(x in -8000i16..8000, y in -8000i16..8000) {
+        corners::<Direction4, _>(Position4 { x, y }, 4)?;
+        corners::<Direction6, _>(Position6Axial { x, y }, 3)?;
+        corners::<Direction6, _>(Position6Cube::from(Position6Axial { x, y }), 3)?;
+    }
+
+    #[test]
+    fn borders_agree_across_edges(
+        walls in proptest::collection::vec(((-4i16..4, -4i16..4), 0usize..6), 0..40),
+        tile in (-4i16..4, -4i16..4),
+    ) {
+        let square: HashSet<Edge4> = walls.iter().map(|&(t, s)| Edge::new(Position4::from(t), Direction4::from_index(s % 4).unwrap())).collect();
+        let hex: HashSet<Edge6> = walls.iter().map(|&(t, s)| Edge::new(Position6Axial::from(t), Direction6::from_index(s).unwrap())).collect();
+        let t = Position4::from(tile);
+        let borders: Borders4 = Edge::borders(&t, |e| square.contains(e));
+        for side in Direction4::iter() {
+            let across: Borders4 = Edge::borders(&t.neighbour(side), |e| square.contains(e));
+            prop_assert_eq!(borders[side], across[side.opposite()]);
+        }
+        let t = Position6Axial::from(tile);
+        let borders: Borders6 = Edge::borders(&t, |e| hex.contains(e));
+        let mut blocked = Obstacles::new(1);
+        blocked.block_borders(&t, &borders);
+        for side in Direction6::iter() {
+            let across: Borders6 = Edge::borders(&t.neighbour(side), |e| hex.contains(e));
+            prop_assert_eq!(borders[side], across[side.opposite()]);
+            prop_assert_eq!(blocked.is_wall(&t, &t.neighbour(side)), hex.contains(&Edge::new(t, side)));
+        }
+    }
+}
+
+#[test]
+fn edges_serialize_canonically() {
+    let edge = Edge::new(Position4 { x: 1i16, y: 2 }, Direction4::West);
+    assert_eq!(edge.tile(), &Position4 { x: 0, y: 2 });
+    assert_eq!(edge.side(), Direction4::East);
+    let json = serde_json::to_string(&edge).unwrap();
+    assert_eq!(json, r#"{"tile":{"x":0,"y":2},"side":0}"#);
+    assert_eq!(serde_json::from_str::<Edge4>(r#"{"tile":{"x":1,"y":2},"side":2}"#).unwrap(), edge);
+    assert!(serde_json::from_str::<Edge4>(r#"{"tile":{"x":32767,"y":2},"side":0}"#).is_err());
+
+    let vertex = Vertex::new(Position6Axial { x: 0i16, y: 0 }, Direction6::NorthWest);
+    let json = serde_json::to_string(&vertex).unwrap();
+    assert_eq!(serde_json::from_str::<Vertex6>(&json).unwrap(), vertex);
+    assert!(serde_json::from_str::<Vertex6>(r#"{"tile":{"x":0,"y":32767},"side":1}"#).is_err());
+    assert_eq!(Vertex::checked_new(Position4 { x: i16::MIN, y: 0 }, Direction4::South), None);
+}
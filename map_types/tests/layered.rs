@@ -0,0 +1,150 @@
+use map_types::*;
+use proptest::prelude::*;
+use structured_digest::{from_canonical_bytes, Digestable};
+
+fn layered() -> impl Strategy<Value = Layered<Position4>> {
+    (-8000i16..8000, -8000i16..8000, -100i16..100).prop_map(|(x, y, level)| Layered::new(Position4 { x, y }, level))
+}
+
+proptest! {
+    #[test]
+    fn neighbours_stay_on_the_level(a in layered()) {
+        let neighbours = a.neighbours();
+        prop_assert_eq!(neighbours.len(), 4);
+        for n in neighbours.iter() {
+            prop_assert_eq!(n.level, a.level);
+            prop_assert_eq!(a.field_length(n), 1);
+        }
+        prop_assert_eq!(a.checked_neighbours(), neighbours);
+    }
+
+    #[test]
+    fn distances_add_levels(a in layered(), b in layered()) {
+        let planar = a.position.field_length(&b.position);
+        let climb = u32::from((a.level - b.level).unsigned_abs());
+        prop_assert_eq!(a.field_length(&b), planar + climb);
+        prop_assert_eq!(b.field_length(&a), a.field_length(&b));
+        prop_assert!(a.line_length(&b) <= f64::from(a.field_length(&b)) + 1e-9);
+        prop_assert!(a.line_length(&b) >= a.position.line_length(&b.position) - 1e-9);
+        let links = VerticalLinks::new(3);
+        prop_assert_eq!(links.distance(&a, &b), a.field_length(&b));
+    }
+
+    #[test]
+    fn group_laws(a in layered(), b in layered(), c in layered()) {
+        let small = |p: Layered<Position4>| Layered::new(Position4 { x: p.position.x / 4, y: p.position.y / 4 }, p.level);
+        let (a, b, c) = (small(a), small(b), small(c));
+        prop_assert_eq!((a + b) + c, a + (b + c));
+        prop_assert_eq!(a + b, b + a);
+        prop_assert_eq!(a - b + b, a);
+        prop_assert_eq!(a + -a, Layered::new(Position4 { x: 0, y: 0 }, 0));
+        prop_assert_eq!(a.checked_add(&b), Some(a + b));
+        prop_assert_eq!(a.checked_sub(&b), Some(a - b));
+        prop_assert_eq!((a + c).field_length(&(b + c)), a.field_length(&b));
+    }
+
+    #[test]
+    fn stairs_change_levels(x in -5i16..5, y in -5i16..5, climb_cost in 1u32..6) {
+        let stairs = Position4 { x, y };
+        let mut links = VerticalLinks::new(climb_cost);
+        links.link(stairs, 0, 1);
+        let (start, goal) = (Layered::new(Position4 { x: -6, y: 0 }, 0), Layered::new(Position4 { x: 6, y: 0 }, 1));
+        let terrain = Obstacles::new(1);
+        let path = astar_with(&start, &goal, &terrain, &links).unwrap();
+        let planar = start.position.field_length(&stairs) + stairs.field_length(&goal.position);
+        prop_assert_eq!(path.cost, planar + climb_cost);
+        prop_assert!(path.tiles.contains(&Layered::new(stairs, 0)));
+        prop_assert!(path.tiles.contains(&Layered::new(stairs, 1)));
+        prop_assert_eq!(dijkstra_with(&start, &goal, &terrain, &links).map(|p| p.cost), Some(path.cost));
+        let back = astar_with(&goal, &start, &terrain, &links).unwrap();
+        prop_assert_eq!(back.cost, path.cost);
+    }
+
+    #[test]
+    fn levels_keep_their_plane(a in layered(), b in layered(), steps in -6i32..6, size in 1.0f64..50.0) {
+        let layout = Layout::pointy_top(size, nalgebra::Point2::new(0.0, 0.0));
+        let hex = |p: Layered<Position4>| Layered::new(Position6Axial { x: p.position.x / 8, y: p.position.y / 8 }, p.level);
+        let (a, b) = (hex(a), hex(b));
+        prop_assert_eq!(layout.to_pixel(&a), layout.to_pixel(&a.position));
+        prop_assert_eq!(layout.corners(&a), layout.corners(&a.position));
+        prop_assert_eq!(layout.from_pixel::<Layered<Position6Axial>>(&layout.to_pixel(&a)), Layered::new(a.position, 0));
+
+        let rotated = a.rotate(&b, steps);
+        prop_assert_eq!(rotated, Layered::new(a.position.rotate(&b.position, steps), a.level));
+        prop_assert_eq!(a.reflect(&b, Axis6::Y), Layered::new(a.position.reflect(&b.position, Axis6::Y), a.level));
+
+        let line: Vec<_> = a.line_to(&b).collect();
+        prop_assert_eq!(line[0], a);
+        prop_assert_eq!(*line.last().unwrap(), b);
+        for w in line.windows(2) {
+            prop_assert!(w[0].position.field_length(&w[1].position) <= 1);
+            prop_assert!((w[0].level - w[1].level).abs() <= 1);
+        }
+        let flat: Vec<_> = a.line_to(&Layered::new(b.position, a.level)).collect();
+        prop_assert_eq!(flat.iter().map(|t| t.position).collect::<Vec<_>>(), a.position.line_to(&b.position).collect::<Vec<_>>());
+        prop_assert!(flat.iter().all(|t| t.level == a.level));
+    }
+
+    #[test]
+    fn encodings_round_trip(a in layered()) {
+        prop_assert_eq!(from_canonical_bytes::<Layered<Position4>>(&a.to_canonical_bytes()), Ok(a));
+        let json = serde_json::to_string(&a).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Layered<Position4>>(&json).unwrap(), a);
+        let hex = Layered::new(Position6Axial { x: i32::from(a.position.x), y: i32::from(a.position.y) }, a.level);
+        let json = serde_json::to_string(&hex).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Layered<Position6Axial<i32>>>(&json).unwrap(), hex);
+    }
+}
+
+#[test]
+fn pits_only_lead_down() {
+    let pit = Position4 { x: 0, y: 0 };
+    let mut links = VerticalLinks::new(1);
+    links.link_one_way(pit, 0, -1);
+    let (top, bottom) = (Layered::new(pit, 0), Layered::new(pit, -1));
+    assert_eq!(links.levels_from(&top), &[-1]);
+    assert!(links.levels_from(&bottom).is_empty());
+    let terrain = Obstacles::new(1);
+    assert_eq!(astar_with(&top, &bottom, &terrain, &links).map(|p| p.cost), Some(1));
+    let up = reachable_with(&bottom, &terrain, 10, &links);
+    assert!(!up.contains_key(&top));
+    assert!(reachable_with(&top, &terrain, 1, &links).contains_key(&bottom));
+}
+
+#[test]
+fn levels_see_their_own_floor() {
+    let viewer = Layered::new(Position4 { x: 0, y: 0 }, 2);
+    let mut sight = Obstacles::new(1);
+    sight.block_tile(Layered::new(Position4 { x: 1, y: 0 }, 2));
+    let seen = field_of_view(&viewer, 3, &sight, Vision::Symmetric);
+    let planar = field_of_view(&viewer.position, 3, &Obstacles::new(1), Vision::Symmetric);
+    assert!(seen.iter().all(|t| t.level == 2));
+    assert!(seen.contains(&Layered::new(Position4 { x: 1, y: 0 }, 2)));
+    assert!(!seen.contains(&Layered::new(Position4 { x: 3, y: 0 }, 2)));
+    assert!(seen.len() < planar.len());
+    let column: Vec<_> = viewer.line_to(&Layered::new(viewer.position, -1)).map(|t| t.level).collect();
+    assert_eq!(column, vec![2, 1, 0, -1]);
+}
+
+#[test]
+fn levels_change_ids() {
+    let ladder = |level| Change {
+        parents: vec![],
+        changes: vec![EntityChange {
+            id: 1,
+            ent_type: Some("ladder".into()),
+            variant: None,
+            orientation: None,
+            base_position: Some(Layered::new(Position6Axial { x: 2i16, y: -1 }, level)),
+            elements: None,
+        }],
+    };
+    let (ground, roof) = (ChangeMessage::from(ladder(0)), ChangeMessage::from(ladder(3)));
+    assert_ne!(ground.id, roof.id);
+    assert_ne!(ground.canonical_bytes(), roof.canonical_bytes());
+
+    let roof = Layered::new(Position6Axial { x: 2i16, y: -1 }, 3);
+    assert_ne!(roof.to_canonical_bytes(), roof.position.to_canonical_bytes());
+    assert_ne!(Layered::new(roof.position, 0).to_canonical_bytes(), roof.position.to_canonical_bytes());
+    assert_eq!(serde_json::to_string(&roof).unwrap(), r#"{"position":{"x":2,"y":-1},"level":3}"#);
+}
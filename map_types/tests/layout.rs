@@ -0,0 +1,43 @@
+use map_types::*;
+use nalgebra::{Point2, Vector2};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn pixels_round_trip(
+        x in -2000i16..2000,
+        y in -2000i16..2000,
+        size in 1.0f64..50.0,
+        origin in (-500.0f64..500.0, -500.0f64..500.0),
+        jitter in (0.0f64..1.0, 0.0f64..std::f64::consts::TAU),
+    ) {
+        let origin = Point2::new(origin.0, origin.1);
+        // inside the circle touching the edges of the tile
+        let offset = Vector2::new(jitter.1.cos(), jitter.1.sin()) * jitter.0 * size * 0.86;
+        let square = Layout::square(size, origin);
+        let p = Position4 { x, y };
+        prop_assert_eq!(square.from_pixel::<Position4>(&square.to_pixel(&p)), p);
+        prop_assert_eq!(square.from_pixel::<Position4>(&(square.to_pixel(&p) + offset)), p);
+        for layout in [Layout::pointy_top(size, origin), Layout::flat_top(size, origin)] {
+            let h = Position6Axial { x, y };
+            let center = layout.to_pixel(&h);
+            prop_assert_eq!(layout.from_pixel::<Position6Axial>(&center), h);
+            prop_assert_eq!(layout.from_pixel::<Position6Axial>(&(center + offset)), h);
+            prop_assert_eq!(layout.from_pixel::<Position6Cube>(&(center + offset)), h.to_cube());
+            let corners = layout.corners(&h);
+            prop_assert_eq!(corners.len(), 6);
+            for c in corners.iter() {
+                prop_assert!(((c - center).norm() - size).abs() < 1e-6);
+            }
+            // neighbours share two corners
+            for n in h.neighbours() {
+                let shared = layout.corners(&n).iter().filter(|a| corners.iter().any(|b| (*a - b).norm() < 1e-6)).count();
+                prop_assert_eq!(shared, 2);
+            }
+        }
+        for n in p.neighbours() {
+            let shared = square.corners(&n).iter().filter(|a| square.corners(&p).iter().any(|b| (*a - b).norm() < 1e-6)).count();
+            prop_assert_eq!(shared, 2);
+        }
+    }
+}
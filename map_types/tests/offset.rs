@@ -0,0 +1,89 @@
+use map_types::*;
+use nalgebra::Point2;
+use proptest::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+const OFFSETS: [OffsetKind; 4] = [
+    OffsetKind::OddR,
+    OffsetKind::EvenR,
+    OffsetKind::OddQ,
+    OffsetKind::EvenQ,
+];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Token {
+    #[serde(with = "map_types::hex_repr::odd_r")]
+    at: Position6Axial,
+    #[serde(with = "map_types::hex_repr::doubled_height")]
+    facing: Position6Axial,
+}
+
+proptest! {
+    #[test]
+    fn offsets_round_trip(x in any::<i16>(), y in any::<i16>()) {
+        let a = Position6Axial { x, y };
+        for kind in OFFSETS {
+            if let Some(o) = Position6Offset::from_axial(kind, &a) {
+                prop_assert_eq!(Position6Axial::try_from(o), Ok(a));
+            }
+        }
+        for kind in [DoubledKind::Width, DoubledKind::Height] {
+            if let Some(d) = Position6Doubled::from_axial(kind, &a) {
+                prop_assert_eq!((i32::from(d.col) + i32::from(d.row)) % 2, 0);
+                prop_assert_eq!(Position6Axial::try_from(d), Ok(a));
+            }
+        }
+    }
+
+    #[test]
+    fn offsets_cover_the_grid(col in -8000i16..8000, row in -8000i16..8000) {
+        for kind in OFFSETS {
+            let o = Position6Offset { kind, col, row };
+            let a = o.to_axial().unwrap();
+            prop_assert_eq!(Position6Offset::from_axial(kind, &a), Some(o));
+        }
+        let d = Position6Doubled { kind: DoubledKind::Width, col, row };
+        prop_assert_eq!(d.to_axial().is_some(), (col + row) % 2 == 0);
+    }
+
+    #[test]
+    fn offsets_match_the_layout(x in -4000i16..4000, y in -4000i16..4000) {
+        let a = Position6Axial { x, y };
+        let shove = |shoved: bool| if shoved { 0.5 } else { 0.0 };
+        let pointy = Layout::pointy_top(1.0, Point2::origin()).to_pixel(&a);
+        let flat = Layout::flat_top(1.0, Point2::origin()).to_pixel(&a);
+        let sqrt3 = 3f64.sqrt();
+        for kind in OFFSETS {
+            let o = Position6Offset::from_axial(kind, &a).unwrap();
+            let (col, row) = (f64::from(o.col), f64::from(o.row));
+            let (px, py) = match kind {
+                OffsetKind::OddR => (sqrt3 * (col + shove(o.row % 2 != 0)), 1.5 * row),
+                OffsetKind::EvenR => (sqrt3 * (col - shove(o.row % 2 != 0)), 1.5 * row),
+                OffsetKind::OddQ => (1.5 * col, sqrt3 * (row + shove(o.col % 2 != 0))),
+                OffsetKind::EvenQ => (1.5 * col, sqrt3 * (row - shove(o.col % 2 != 0))),
+            };
+            let pixel = if o.kind == OffsetKind::OddR || o.kind == OffsetKind::EvenR { pointy } else { flat };
+            prop_assert!((pixel.x - px).abs() < 1e-6 && (pixel.y - py).abs() < 1e-6);
+        }
+        let d = Position6Doubled::from_axial(DoubledKind::Width, &a).unwrap();
+        prop_assert!((pointy.x - sqrt3 / 2.0 * f64::from(d.col)).abs() < 1e-6);
+        let d = Position6Doubled::from_axial(DoubledKind::Height, &a).unwrap();
+        prop_assert!((flat.y - sqrt3 / 2.0 * f64::from(d.row)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn serde_representations(x in -8000i16..8000, y in -8000i16..8000) {
+        let token = Token { at: Position6Axial { x, y }, facing: Position6Axial { x: y, y: x } };
+        let json = serde_json::to_value(&token).unwrap();
+        let at = Position6Offset::from_axial(OffsetKind::OddR, &token.at).unwrap();
+        prop_assert_eq!(&json["at"], &serde_json::json!({"col": at.col, "row": at.row}));
+        prop_assert_eq!(serde_json::from_value::<Token>(json).unwrap(), token);
+    }
+}
+
+#[test]
+fn odd_doubled_coordinates_are_rejected() {
+    let json = r#"{"at":{"col":0,"row":0},"facing":{"col":1,"row":0}}"#;
+    assert!(serde_json::from_str::<Token>(json).is_err());
+}
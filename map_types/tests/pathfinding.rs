@@ -0,0 +1,90 @@
+use map_types::*;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn astar_finds_cheapest_paths(
+        blocked in proptest::collection::vec((-6i16..6, -6i16..6), 0..30),
+        costly in proptest::collection::vec(((-6i16..6, -6i16..6), 1u32..5), 0..20),
+        goal in (-6i16..6, -6i16..6),
+    ) {
+        let mut square = Obstacles::new(1);
+        let mut hex = Obstacles::new(1);
+        for &b in blocked.iter().filter(|&&b| b != (0, 0) && b != goal) {
+            square.block_tile(Position4::from(b));
+            hex.block_tile(Position6Axial::from(b));
+        }
+        for &(c, cost) in costly.iter() {
+            square.set_cost(Position4::from(c), cost);
+            hex.set_cost(Position6Axial::from(c), cost);
+        }
+        // fence everything in so failed searches end
+        for p in Position4::from((0, 0)).ring(7, Metric4::Chebyshev) {
+            square.block_tile(p);
+        }
+        for p in Position6Axial::from((0, 0)).ring(13) {
+            hex.block_tile(p);
+        }
+        let (start, goal4) = (Position4::from((0, 0)), Position4::from(goal));
+        let a = astar(&start, &goal4, &square);
+        let d = dijkstra(&start, &goal4, &square);
+        prop_assert_eq!(a.as_ref().map(|p| p.cost), d.as_ref().map(|p| p.cost));
+        prop_assert_eq!(reachable(&start, &square, u32::MAX).get(&goal4).copied(), d.as_ref().map(|p| p.cost));
+        if let Some(path) = a {
+            prop_assert_eq!(path.tiles[0], start);
+            prop_assert_eq!(*path.tiles.last().unwrap(), goal4);
+            prop_assert!(path.cost >= start.field_length(&goal4));
+            for w in path.tiles.windows(2) {
+                prop_assert_eq!(w[0].field_length(&w[1]), 1);
+                prop_assert!(!square.is_blocked(&w[1]));
+            }
+        }
+        let (start, goal6) = (Position6Axial::from((0, 0)), Position6Axial::from(goal));
+        let a = astar(&start, &goal6, &hex);
+        prop_assert_eq!(a.as_ref().map(|p| p.cost), dijkstra(&start, &goal6, &hex).map(|p| p.cost));
+        if let Some(path) = a {
+            prop_assert!(path.tiles.windows(2).all(|w| w[0].field_length(&w[1]) == 1));
+        }
+    }
+
+    #[test]
+    fn walls_block_edges(x in -5i16..5) {
+        let mut walls = Obstacles::new(1);
+        for y in -10..10 {
+            walls.block_edge(Position4 { x, y }, Position4 { x: x + 1, y });
+        }
+        let (a, b) = (Position4 { x, y: 0 }, Position4 { x: x + 1, y: 0 });
+        let path = astar(&a, &b, &walls).unwrap();
+        prop_assert_eq!(path.cost, 21);
+        prop_assert!(path.tiles.windows(2).all(|w| !walls.is_wall(&w[0], &w[1])));
+        prop_assert!(!reachable(&a, &walls, 5).contains_key(&b));
+    }
+
+    #[test]
+    fn metrics_price_steps(
+        blocked in proptest::collection::vec((-6i16..6, -6i16..6), 0..30),
+        goal in (-6i16..6, -6i16..6),
+    ) {
+        let mut square = Obstacles::new(1);
+        for &b in blocked.iter().filter(|&&b| b != (0, 0) && b != goal) {
+            square.block_tile(Position4::from(b));
+        }
+        for p in Position4::from((0, 0)).ring(7, Metric4::Chebyshev) {
+            square.block_tile(p);
+        }
+        let (start, goal) = (Position4::from((0, 0)), Position4::from(goal));
+        for metric in [Metric4::Manhattan, Metric4::Chebyshev, Metric4::Alternating, Metric4::Euclidean] {
+            let a = astar_with(&start, &goal, &square, &metric);
+            let d = dijkstra_with(&start, &goal, &square, &metric);
+            prop_assert_eq!(a.as_ref().map(|p| p.cost), d.as_ref().map(|p| p.cost));
+            prop_assert_eq!(reachable_with(&start, &square, u32::MAX, &metric).get(&goal).copied(), d.as_ref().map(|p| p.cost));
+            if let Some(path) = a {
+                prop_assert!(path.cost >= metric.cost_estimate(&start, &goal));
+                let steps: u32 = path.tiles.windows(2).map(|w| metric.step_cost(&w[0], &w[1])).sum();
+                prop_assert_eq!(path.cost, steps);
+            }
+        }
+        let manhattan = astar_with(&start, &goal, &square, &Metric4::Manhattan).map(|p| p.cost);
+        prop_assert_eq!(manhattan, astar(&start, &goal, &square).map(|p| p.cost));
+    }
+}
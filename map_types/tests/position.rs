@@ -0,0 +1,316 @@
+use map_types::*;
+use proptest::prelude::*;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use structured_digest::{from_canonical_bytes, Digestable};
+
+/// Axial positions whose cube coordinates, and those of sums of two of them, fit into i16.
+fn axial() -> impl Strategy<Value = Position6Axial> {
+    (-8000i16..8000, -8000i16..8000).prop_map(Position6Axial::from)
+}
+
+/// Small enough that rotating or mirroring one around another stays in range.
+fn small_axial() -> impl Strategy<Value = Position6Axial> {
+    (-4000i16..4000, -4000i16..4000).prop_map(Position6Axial::from)
+}
+
+fn cube() -> impl Strategy<Value = Position6Cube> {
+    axial().prop_map(Position6Cube::from)
+}
+
+fn pos4() -> impl Strategy<Value = Position4> {
+    any::<(i16, i16)>().prop_map(Position4::from)
+}
+
+const METRICS: [Metric4; 4] = [
+    Metric4::Manhattan,
+    Metric4::Chebyshev,
+    Metric4::Alternating,
+    Metric4::Euclidean,
+];
+
+proptest! {
+    #[test]
+    fn axial_cube_roundtrip(a in axial()) {
+        let c = a.to_cube();
+        prop_assert_eq!(i32::from(c.x) + i32::from(c.y) + i32::from(c.z), 0);
+        prop_assert_eq!(c.to_axial(), a);
+        prop_assert_eq!(Position6Cube::from(a), c);
+        prop_assert_eq!(a.checked_to_cube(), Some(c));
+    }
+
+    #[test]
+    fn checked_to_cube_overflow(x in any::<i16>(), y in any::<i16>()) {
+        let z = -(i32::from(x) + i32::from(y));
+        let fits = z >= i32::from(i16::MIN) && z <= i32::from(i16::MAX);
+        prop_assert_eq!(Position6Axial { x, y }.checked_to_cube().is_some(), fits);
+    }
+
+    #[test]
+    fn hex_distances_agree(a in axial(), b in axial()) {
+        let d = a.field_length(&b);
+        prop_assert_eq!(a.to_cube().field_length(&b.to_cube()), d);
+        prop_assert_eq!(a.field_length(&b.to_cube()), d);
+        prop_assert_eq!(a.to_cube().field_length(&b), d);
+        prop_assert_eq!(b.field_length(&a), d);
+        prop_assert!((a.line_length(&b) - a.to_cube().line_length(&b.to_cube())).abs() < 1e-9);
+        prop_assert!(a.line_length(&b) <= f64::from(d) + 1e-9);
+    }
+
+    #[test]
+    fn hex_triangle_inequality(a in axial(), b in axial(), c in axial()) {
+        prop_assert!(a.field_length(&c) <= a.field_length(&b) + b.field_length(&c));
+    }
+
+    #[test]
+    fn pos4_distances(a in pos4(), b in pos4()) {
+        let dx = (i32::from(a.x) - i32::from(b.x)).abs();
+        let dy = (i32::from(a.y) - i32::from(b.y)).abs();
+        prop_assert_eq!(a.field_length(&b), (dx + dy) as u32);
+        prop_assert_eq!(b.field_length(&a), a.field_length(&b));
+        prop_assert!(a.line_length(&b) <= f64::from(a.field_length(&b)) + 1e-9);
+    }
+
+    #[test]
+    fn neighbours_are_adjacent(a in axial(), p in (-1000i16..1000, -1000i16..1000)) {
+        let n = a.neighbours();
+        prop_assert_eq!(n.len(), 6);
+        for b in n.iter() {
+            prop_assert_eq!(a.field_length(b), 1);
+        }
+        for b in a.to_cube().neighbours() {
+            prop_assert_eq!(a.field_length(&b), 1);
+            prop_assert_eq!(i32::from(b.x) + i32::from(b.y) + i32::from(b.z), 0);
+        }
+        let p = Position4::from(p);
+        for b in p.neighbours() {
+            prop_assert_eq!(p.field_length(&b), 1);
+            prop_assert!((p.line_length(&b) - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn checked_neighbours_at_edges(x in any::<i16>(), y in any::<i16>()) {
+        let p = Position4 { x, y };
+        let expected = [x != i16::MAX, y != i16::MAX, x != i16::MIN, y != i16::MIN]
+            .iter()
+            .filter(|&&b| b)
+            .count();
+        prop_assert_eq!(p.checked_neighbours().len(), expected);
+        let a = Position6Axial { x, y };
+        for b in a.checked_neighbours() {
+            prop_assert_eq!(a.field_length(&b), 1);
+        }
+    }
+
+    #[test]
+    fn group_laws(a in axial(), b in axial()) {
+        prop_assert_eq!(a + (-a), Position6Axial { x: 0, y: 0 });
+        prop_assert_eq!(-(-a), a);
+        prop_assert_eq!((a + b) - b, a);
+        prop_assert_eq!(a + b, b + a);
+        let (ca, cb) = (a.to_cube(), b.to_cube());
+        prop_assert_eq!(ca + (-ca), Position6Cube { x: 0, y: 0, z: 0 });
+        prop_assert_eq!((ca + cb) - cb, ca);
+        prop_assert_eq!(-ca, (-a).to_cube());
+    }
+
+    #[test]
+    fn mixed_ops_agree(a in axial(), b in axial()) {
+        let c = a.to_cube();
+        prop_assert_eq!(c + b, (a + b).to_cube());
+        prop_assert_eq!(c - b, (a - b).to_cube());
+        let mut d = c;
+        d += b;
+        prop_assert_eq!(d, c + b);
+        d -= b;
+        prop_assert_eq!(d, c);
+        let mut e = a;
+        e += b.to_cube();
+        prop_assert_eq!(e, a + b);
+        e -= b.to_cube();
+        prop_assert_eq!(e, a);
+    }
+
+    #[test]
+    fn checked_matches_plain(a in pos4(), b in pos4()) {
+        let sum = (i32::from(a.x) + i32::from(b.x), i32::from(a.y) + i32::from(b.y));
+        let fits = |v: i32| v >= i32::from(i16::MIN) && v <= i32::from(i16::MAX);
+        match a.checked_add(&b) {
+            Some(c) => prop_assert_eq!((i32::from(c.x), i32::from(c.y)), sum),
+            None => prop_assert!(!fits(sum.0) || !fits(sum.1)),
+        }
+        let s = a.saturating_add(b);
+        prop_assert_eq!(i32::from(s.x), sum.0.max(i32::from(i16::MIN)).min(i32::from(i16::MAX)));
+        let diff = (i32::from(a.x) - i32::from(b.x), i32::from(a.y) - i32::from(b.y));
+        prop_assert_eq!(a.checked_sub(&b).is_some(), fits(diff.0) && fits(diff.1));
+        prop_assert_eq!(a.checked_neg().is_none(), a.x == i16::MIN || a.y == i16::MIN);
+    }
+
+    #[test]
+    fn cube_encoding_roundtrip(c in cube()) {
+        prop_assert_eq!(from_canonical_bytes::<Position6Cube>(&c.to_canonical_bytes()).unwrap(), c);
+        let shifted = Position6Cube { z: c.z.wrapping_add(1), ..c };
+        prop_assert_ne!(shifted.to_canonical_bytes(), c.to_canonical_bytes());
+    }
+
+    #[test]
+    fn rotations_preserve_distance(a in small_axial(), b in small_axial(), p in small_axial(), steps in -12i32..12) {
+        let (ra, rb) = (a.rotate(&p, steps), b.rotate(&p, steps));
+        prop_assert_eq!(ra.field_length(&rb), a.field_length(&b));
+        prop_assert_eq!(ra.field_length(&p), a.field_length(&p));
+        prop_assert_eq!(ra.rotate(&p, -steps), a);
+        prop_assert_eq!(a.rotate(&p, steps + 6), ra);
+        prop_assert_eq!(a.to_cube().rotate(&p.to_cube(), steps), ra.to_cube());
+        let q = Position4 { x: a.x, y: a.y };
+        let pivot = Position4 { x: p.x, y: p.y };
+        prop_assert_eq!(q.rotate(&pivot, 1).rotate(&pivot, 3), q);
+        prop_assert_eq!(q.rotate(&pivot, steps).field_length(&pivot), q.field_length(&pivot));
+    }
+
+    #[test]
+    fn reflections_are_involutions(a in small_axial(), p in small_axial()) {
+        for axis in [Axis6::X, Axis6::Y, Axis6::Z] {
+            let r = a.reflect(&p, axis);
+            prop_assert_eq!(r.reflect(&p, axis), a);
+            prop_assert_eq!(r.field_length(&p), a.field_length(&p));
+        }
+        let origin = Position6Cube { x: 0, y: 0, z: 0 };
+        let along = [(Axis6::X, Position6Cube { x: 0, y: 1, z: -1 }), (Axis6::Y, Position6Cube { x: 1, y: 0, z: -1 }), (Axis6::Z, Position6Cube { x: 1, y: -1, z: 0 })];
+        for (axis, step) in along {
+            prop_assert_eq!(step.reflect(&origin, axis), step);
+            prop_assert_eq!((-step).reflect(&origin, axis), -step);
+            let on_axis = p + Position6Axial::from_cube(step);
+            prop_assert_eq!(on_axis.reflect(&p, axis), on_axis);
+        }
+        let q = Position4 { x: a.x, y: a.y };
+        let pivot = Position4 { x: p.x, y: p.y };
+        for axis in [Axis4::X, Axis4::Y] {
+            prop_assert_eq!(q.reflect(&pivot, axis).reflect(&pivot, axis), q);
+        }
+        prop_assert_eq!(q.reflect(&pivot, Axis4::X).reflect(&pivot, Axis4::Y), q.rotate(&pivot, 2));
+    }
+
+    #[test]
+    fn lines_are_connected(a in small_axial(), b in small_axial()) {
+        let hex: Vec<_> = a.line_to(&b).collect();
+        prop_assert_eq!(hex.len() as u32, a.field_length(&b) + 1);
+        prop_assert_eq!(hex[0], a);
+        prop_assert_eq!(*hex.last().unwrap(), b);
+        for w in hex.windows(2) {
+            prop_assert_eq!(w[0].field_length(&w[1]), 1);
+        }
+        let (p, q) = (Position4 { x: a.x, y: a.y }, Position4 { x: b.x, y: b.y });
+        let line: Vec<_> = p.line_to(&q).collect();
+        prop_assert_eq!(line[0], p);
+        prop_assert_eq!(*line.last().unwrap(), q);
+        for w in line.windows(2) {
+            prop_assert!((w[0].x - w[1].x).abs() <= 1 && (w[0].y - w[1].y).abs() <= 1);
+        }
+        let cover: Vec<_> = p.supercover_to(&q).collect();
+        prop_assert_eq!(cover[0], p);
+        prop_assert_eq!(*cover.last().unwrap(), q);
+        prop_assert!(cover.len() >= line.len());
+        for w in cover.windows(2) {
+            prop_assert!((w[0].x - w[1].x).abs() <= 1 && (w[0].y - w[1].y).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn hex_areas(a in axial(), radius in 0u32..12) {
+        let within: Vec<_> = a.within(radius).collect();
+        prop_assert_eq!(within.len() as u32, 3 * radius * (radius + 1) + 1);
+        prop_assert!(within.iter().all(|b| a.field_length(b) <= radius));
+        let ring: Vec<_> = a.ring(radius).collect();
+        prop_assert_eq!(ring.len() as u32, (6 * radius).max(1));
+        prop_assert!(ring.iter().all(|b| a.field_length(b) == radius));
+        for w in ring.windows(2) {
+            prop_assert_eq!(w[0].field_length(&w[1]), 1);
+        }
+        let mut spiral: Vec<_> = a.to_cube().spiral(radius).map(|c| c.to_axial()).collect();
+        prop_assert!(spiral.windows(2).all(|w| a.field_length(&w[0]) <= a.field_length(&w[1])));
+        let mut within = within;
+        let key = |p: &Position6Axial| (p.x, p.y);
+        spiral.sort_by_key(key);
+        within.sort_by_key(key);
+        prop_assert_eq!(spiral, within);
+    }
+
+    #[test]
+    fn square_metrics(a in pos4(), b in pos4()) {
+        let (dx, dy) = (i64::from(a.x) - i64::from(b.x), i64::from(a.y) - i64::from(b.y));
+        let (long, short) = (dx.abs().max(dy.abs()), dx.abs().min(dy.abs()));
+        prop_assert_eq!(Natural.distance(&a, &b), a.field_length(&b));
+        prop_assert_eq!(i64::from(Metric4::Manhattan.distance(&a, &b)), long + short);
+        prop_assert_eq!(i64::from(Metric4::Chebyshev.distance(&a, &b)), long);
+        prop_assert_eq!(i64::from(Metric4::Alternating.distance(&a, &b)), long + short / 2);
+        let euclidean = ((dx * dx + dy * dy) as f64).sqrt().round() as i64;
+        prop_assert_eq!(i64::from(Metric4::Euclidean.distance(&a, &b)), euclidean);
+        for metric in METRICS {
+            prop_assert_eq!(metric.distance(&a, &b), metric.distance(&b, &a));
+            prop_assert!(metric.neighbours(&a).iter().all(|n| metric.distance(&a, n) == 1));
+            let count = if metric == Metric4::Manhattan { 4 } else { 8 };
+            prop_assert!(metric.neighbours(&a).len() <= count);
+        }
+    }
+
+    #[test]
+    fn square_areas(x in -1000i16..1000, y in -1000i16..1000, radius in 0u32..12) {
+        let p = Position4 { x, y };
+        let r = i64::from(radius);
+        for metric in METRICS {
+            let inside = |b: &Position4| {
+                let (dx, dy) = (i64::from(b.x - p.x).abs(), i64::from(b.y - p.y).abs());
+                let (long, short) = (dx.max(dy), dx.min(dy));
+                match metric {
+                    Metric4::Manhattan => dx + dy <= r,
+                    Metric4::Chebyshev => long <= r,
+                    Metric4::Alternating => long + short / 2 <= r,
+                    // rounded half up, the centers less than half a tile outside count
+                    Metric4::Euclidean => dx * dx + dy * dy <= r * r + r,
+                }
+            };
+            let within: HashSet<_> = p.within(radius, metric).collect();
+            let r16 = i16::try_from(radius).unwrap();
+            let expected: HashSet<_> = (x - r16 - 1..=x + r16 + 1)
+                .flat_map(|x| (y - r16 - 1..=y + r16 + 1).map(move |y| Position4 { x, y }))
+                .filter(|b| inside(b))
+                .collect();
+            prop_assert_eq!(&within, &expected);
+            prop_assert_eq!(p.within(radius, metric).count(), within.len());
+            let ring: Vec<_> = p.ring(radius, metric).collect();
+            let inner = if radius == 0 { 0 } else { p.within(radius - 1, metric).count() };
+            prop_assert_eq!(ring.len() + inner, within.len());
+            prop_assert_eq!(p.spiral(radius, metric).count(), within.len());
+            prop_assert_eq!(p.spiral(radius, metric).next(), Some(p));
+        }
+    }
+
+    #[test]
+    fn areas_skip_unrepresentable(radius in 0u32..5) {
+        let corner = Position4 { x: i16::MAX, y: i16::MIN };
+        let r = radius as usize;
+        prop_assert_eq!(corner.within(radius, Metric4::Chebyshev).count(), (r + 1) * (r + 1));
+    }
+}
+
+#[test]
+fn square_areas_have_known_sizes() {
+    let p = Position4 { x: 0, y: 0 };
+    let sizes = [
+        (Metric4::Manhattan, [1, 5, 13, 25, 41]),
+        (Metric4::Chebyshev, [1, 9, 25, 49, 81]),
+        (Metric4::Alternating, [1, 9, 21, 37, 61]),
+        (Metric4::Euclidean, [1, 9, 21, 37, 69]),
+    ];
+    for (metric, sizes) in sizes.iter() {
+        for (radius, size) in sizes.iter().enumerate() {
+            assert_eq!(p.within(radius as u32, *metric).count(), *size, "{:?} {}", metric, radius);
+        }
+    }
+    let disk: Vec<_> = p.within(2, Metric4::Euclidean).collect();
+    assert!(disk.contains(&Position4 { x: 2, y: 1 }));
+    assert!(!disk.contains(&Position4 { x: 2, y: 2 }));
+    let alternating: Vec<_> = p.within(2, Metric4::Alternating).collect();
+    assert!(alternating.contains(&Position4 { x: 1, y: 1 }) && !alternating.contains(&Position4 { x: 2, y: 2 }));
+}
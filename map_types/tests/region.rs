@@ -0,0 +1,126 @@
+use map_types::*;
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+fn tiles() -> impl Strategy<Value = Region<Position4>> {
+    proptest::collection::vec((-6i16..6, -6i16..6), 0..50).prop_map(|t| t.into_iter().map(Position4::from).collect())
+}
+
+proptest! {
+    #[test]
+    fn set_operations(a in tiles(), b in tiles()) {
+        let (sa, sb): (HashSet<_>, HashSet<_>) = (a.iter().copied().collect(), b.iter().copied().collect());
+        prop_assert_eq!(&a | &b, sa.union(&sb).copied().collect());
+        prop_assert_eq!(&a & &b, sa.intersection(&sb).copied().collect());
+        prop_assert_eq!(&a - &b, sa.difference(&sb).copied().collect());
+        prop_assert_eq!(&a ^ &b, sa.symmetric_difference(&sb).copied().collect());
+        prop_assert_eq!((&a - &b).union(&a.intersection(&b)), a.clone());
+        prop_assert_eq!(a.xor(&b), (&a | &b).difference(&(&a & &b)));
+        prop_assert!(a.intersection(&b).is_subset(&a));
+    }
+
+    #[test]
+    fn shapes_have_their_size(x in -100i16..100, y in -100i16..100, w in 0i16..8, h in 0i16..8) {
+        let (a, b) = (Position4 { x, y }, Position4 { x: x + w, y: y - h });
+        let rectangle = Region::rectangle(&a, &b);
+        prop_assert_eq!(rectangle.len(), ((w + 1) * (h + 1)) as usize);
+        let corners = [a, Position4 { x: a.x, y: b.y }, b, Position4 { x: b.x, y: a.y }];
+        prop_assert_eq!(&Region::polygon(&corners), &rectangle);
+        prop_assert_eq!(rectangle.boundary_edges::<Direction4>().len(), (2 * (w + 1) + 2 * (h + 1)) as usize);
+
+        let (a, b) = (Position6Axial { x, y }, Position6Axial { x: x - w, y: y + h });
+        let parallelogram = Region::parallelogram(&a, &b);
+        prop_assert_eq!(parallelogram.len(), ((w + 1) * (h + 1)) as usize);
+        let corners = [a, Position6Axial { x: a.x, y: b.y }, b, Position6Axial { x: b.x, y: a.y }];
+        prop_assert_eq!(Region::polygon(&corners), parallelogram);
+
+        let radius = w as u32;
+        let hexagon = Region::hexagon(&a, radius);
+        prop_assert_eq!(hexagon.len(), (3 * radius * (radius + 1) + 1) as usize);
+        prop_assert_eq!(&hexagon, &a.within(radius).collect());
+        let corners: Vec<_> = Direction6::iter().map(|d| (0..radius).fold(a, |p, _| p.neighbour(d))).collect();
+        prop_assert_eq!(&Region::polygon(&corners), &hexagon);
+        if radius > 0 {
+            prop_assert_eq!(hexagon.boundary(), a.ring(radius).collect());
+        }
+        let cube = Position6Cube::from(a);
+        prop_assert_eq!(Region::hexagon(&cube, radius).len(), hexagon.len());
+    }
+
+    #[test]
+    fn circles_measure_from_centers(x in -100i16..100, y in -100i16..100, radius in 0u32..8) {
+        let p = Position4 { x, y };
+        let disk: Region<_> = p.within(radius, Metric4::Chebyshev).filter(|t| p.line_length(t) <= f64::from(radius)).collect();
+        prop_assert_eq!(Region::circle(&p, radius), disk);
+        let h = Position6Axial { x, y };
+        let disk: Region<_> = h.within(2 * radius).filter(|t| h.line_length(t) <= f64::from(radius)).collect();
+        prop_assert_eq!(Region::circle(&h, radius), disk);
+    }
+
+    #[test]
+    fn components_split_the_region(region in tiles()) {
+        let components = region.components();
+        prop_assert_eq!(components.iter().map(|c| c.len()).sum::<usize>(), region.len());
+        let mut all = Region::new();
+        for c in components.iter() {
+            all.extend(c.iter().copied());
+            let start = c.iter().next().unwrap();
+            prop_assert_eq!(&Region::flood_fill(start, &Fenced(&region), u32::MAX), c);
+            prop_assert!(c.boundary().is_subset(c));
+        }
+        prop_assert_eq!(&all, &region);
+        let inner = &region - &region.boundary();
+        for t in inner.iter() {
+            prop_assert!(t.neighbours().iter().all(|n| region.contains(n)));
+        }
+        prop_assert!(region.components_with(&Metric4::Chebyshev).len() <= components.len());
+    }
+
+    #[test]
+    fn walls_bound_flood_fills(x in -4i16..4, y in -4i16..4, door in 0usize..100) {
+        let room = Region::rectangle(&Position4 { x: -5, y: -5 }, &Position4 { x: 5, y: 5 });
+        let mut walls = room.boundary_edges::<Direction4>();
+        prop_assert_eq!(walls.len(), 44);
+        let start = Position4 { x, y };
+        let mut closed = Obstacles::new(1);
+        for tile in room.iter() {
+            let borders: Borders4 = Edge::borders(tile, |e| walls.contains(e));
+            closed.block_borders(tile, &borders);
+        }
+        prop_assert_eq!(&Region::flood_fill(&start, &closed, u32::MAX), &room);
+
+        let door = walls.remove(door % walls.len());
+        let mut open = Obstacles::new(1);
+        for wall in walls.iter() {
+            let (a, b) = wall.tiles();
+            open.block_edge(a, b);
+        }
+        let flooded = Region::flood_fill(&start, &open, 30);
+        prop_assert!(room.is_subset(&flooded));
+        let (a, b) = door.tiles();
+        prop_assert!(flooded.contains(&a) && flooded.contains(&b));
+        prop_assert!(!flooded.is_subset(&room));
+        prop_assert_eq!(Region::flood_fill(&start, &Obstacles::new(1), 3), start.within(3, Metric4::Manhattan).collect());
+    }
+}
+
+/// Only the tiles of a region can be entered.
+struct Fenced<'a>(&'a Region<Position4>);
+
+impl Terrain<Position4> for Fenced<'_> {
+    fn cost(&self, _from: &Position4, to: &Position4) -> Option<u32> {
+        if self.0.contains(to) {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn regions_serialize_as_lists() {
+    let region: Region<Position4> = vec![Position4 { x: 1, y: 2 }].into_iter().collect();
+    let json = serde_json::to_string(&region).unwrap();
+    assert_eq!(json, r#"[{"x":1,"y":2}]"#);
+    assert_eq!(serde_json::from_str::<Region<Position4>>(&json).unwrap(), region);
+}
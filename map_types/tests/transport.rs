@@ -0,0 +1,58 @@
+use map_types::*;
+use sha2::{Digest, Sha256};
+
+fn change() -> Change<Position4> {
+    let entity = |id| EntityChange {
+        id,
+        ent_type: Some("door".to_string()),
+        variant: None,
+        orientation: Some(1),
+        base_position: Some(Position4 { x: 3, y: -2 }),
+        elements: None,
+    };
+    Change {
+        parents: vec![ChangeId::from([7; 32])],
+        changes: vec![entity(1), entity(2), entity(3)],
+    }
+}
+
+#[test]
+fn latest_ids_hash_the_canonical_bytes() {
+    let message = ChangeMessage::from(change());
+    assert_eq!(message.scheme, HashScheme::LATEST);
+    let bytes = message.canonical_bytes();
+    assert_eq!(message.id, ChangeId::new::<Sha256>(Sha256::digest(&bytes)));
+    let decoded =
+        ChangeMessage::<Position4>::from_canonical_bytes(&bytes, message.scheme, message.id.algorithm).unwrap();
+    assert_eq!(decoded.id, message.id);
+    assert!(decoded.verify());
+    assert!(message.prove_entity::<Sha256>(1).is_none());
+}
+
+#[test]
+fn merkle_ids_are_opt_in() {
+    let message = ChangeMessage::new(change(), HashScheme::MerkleV2).unwrap();
+    assert_ne!(message.id, ChangeId::new::<Sha256>(Sha256::digest(&message.canonical_bytes())));
+    let proof = message.prove_entity::<Sha256>(1).unwrap();
+    assert!(message.change.changes[1].verify_inclusion(&message.id, message.scheme, &proof));
+    assert!(!message.change.changes[0].verify_inclusion(&message.id, message.scheme, &proof));
+}
+
+#[test]
+fn old_schemes_only_take_sha256_parents() {
+    let mut change = change();
+    change.parents.push(ChangeId::new::<blake3::Hasher>(blake3::Hasher::digest(b"parent")));
+    let parent = change.parents[1];
+    assert_eq!(
+        ChangeMessage::new(change.clone(), HashScheme::V0).unwrap_err(),
+        MessageError::UnsupportedParent(parent)
+    );
+    for scheme in [HashScheme::V2, HashScheme::MerkleV2] {
+        let message = ChangeMessage::new(change.clone(), scheme).unwrap();
+        let decoded =
+            ChangeMessage::<Position4>::from_canonical_bytes(&message.canonical_bytes(), scheme, HashAlgorithm::Sha256)
+                .unwrap();
+        assert_eq!(decoded.change.parents, change.parents);
+        assert_eq!(decoded.id, message.id);
+    }
+}
@@ -0,0 +1,99 @@
+use map_types::*;
+use proptest::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use structured_digest::{from_canonical_bytes, Digestable};
+
+fn triangle() -> impl Strategy<Value = Position3> {
+    (-8000i16..8000, -8000i16..8000, any::<bool>()).prop_map(Position3::from)
+}
+
+fn breadth_first(from: Position3, radius: u32) -> HashMap<Position3, u32> {
+    let mut seen = HashMap::new();
+    seen.insert(from, 0);
+    let mut todo = VecDeque::from(vec![from]);
+    while let Some(p) = todo.pop_front() {
+        let d = seen[&p];
+        if d == radius {
+            continue;
+        }
+        for n in p.neighbours() {
+            seen.entry(n).or_insert_with(|| {
+                todo.push_back(n);
+                d + 1
+            });
+        }
+    }
+    seen
+}
+
+proptest! {
+    #[test]
+    fn neighbours_share_edges(a in triangle()) {
+        let neighbours = a.neighbours();
+        prop_assert_eq!(neighbours.len(), 3);
+        for n in neighbours.iter() {
+            prop_assert_ne!(n.up, a.up);
+            prop_assert_eq!(a.field_length(n), 1);
+            prop_assert!((a.line_length(n) - 1.0).abs() < 1e-9);
+            prop_assert!(n.neighbours().contains(&a));
+        }
+        prop_assert_eq!(a.checked_neighbours(), neighbours);
+    }
+
+    #[test]
+    fn distances_count_steps(a in triangle(), dx in -3i16..3, dy in -3i16..3, up in any::<bool>()) {
+        let b = a + Position3 { x: dx, y: dy, up };
+        let steps = breadth_first(a, 13);
+        prop_assert_eq!(steps.get(&b).copied(), Some(a.field_length(&b)));
+        prop_assert_eq!(b.field_length(&a), a.field_length(&b));
+        prop_assert!(a.line_length(&b) <= f64::from(a.field_length(&b)) + 1e-9);
+        let path = astar(&a, &b, &Obstacles::new(1)).unwrap();
+        prop_assert_eq!(path.cost, a.field_length(&b));
+    }
+
+    #[test]
+    fn group_laws(a in triangle(), b in triangle(), c in triangle()) {
+        let small = |p: Position3| Position3 { x: p.x / 4, y: p.y / 4, up: p.up };
+        let (a, b, c) = (small(a), small(b), small(c));
+        prop_assert_eq!((a + b) + c, a + (b + c));
+        prop_assert_eq!(a + b, b + a);
+        prop_assert_eq!(a - b + b, a);
+        prop_assert_eq!(a + -a, Position3::default());
+        prop_assert_eq!(a.checked_add(&b), Some(a + b));
+        prop_assert_eq!(a.checked_sub(&b), Some(a - b));
+        // translations keep distances
+        let t = Position3 { x: c.x, y: c.y, up: false };
+        prop_assert_eq!((a + t).field_length(&(b + t)), a.field_length(&b));
+    }
+
+    #[test]
+    fn encodings_round_trip(a in triangle()) {
+        prop_assert_eq!(from_canonical_bytes::<Position3>(&a.to_canonical_bytes()), Ok(a));
+        let json = serde_json::to_string(&a).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Position3>(&json).unwrap(), a);
+        let wide = Position3 { x: i32::from(a.x), y: i32::from(a.y), up: a.up };
+        prop_assert_eq!(serde_json::from_str::<Position3<i32>>(&serde_json::to_string(&wide).unwrap()).unwrap(), wide);
+    }
+}
+
+#[test]
+fn halves_of_a_rhombus_encode_apart() {
+    let down = Position3 { x: 2i16, y: -1, up: false };
+    let up = Position3 { up: true, ..down };
+    assert_ne!(down.to_canonical_bytes(), up.to_canonical_bytes());
+    assert_ne!(
+        down.to_canonical_bytes(),
+        Position4 { x: 2i16, y: -1 }.to_canonical_bytes()
+    );
+    assert_eq!(serde_json::to_string(&up).unwrap(), r#"{"x":2,"y":-1,"up":true}"#);
+    let wide = Position3 { x: 2i32, y: -1, up: true };
+    let json = serde_json::to_string(&wide).unwrap();
+    assert_ne!(json, serde_json::to_string(&up).unwrap());
+    assert!(serde_json::from_str::<Position3>(&json).is_err());
+}
+
+#[test]
+fn checked_neighbours_stop_at_the_edge() {
+    let corner = Position3 { x: i16::MIN, y: i16::MIN, up: false };
+    assert_eq!(corner.checked_neighbours(), vec![Position3 { x: i16::MIN, y: i16::MIN, up: true }]);
+}
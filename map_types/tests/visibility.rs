@@ -0,0 +1,79 @@
+use map_types::*;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn symmetric_vision_is_symmetric(
+        blocked in proptest::collection::vec((-5i16..5, -5i16..5), 0..25),
+        walls in proptest::collection::vec(((-5i16..5, -5i16..5), 0usize..4), 0..25),
+        a in (-5i16..5, -5i16..5),
+        b in (-5i16..5, -5i16..5),
+    ) {
+        let mut square = Obstacles::new(1);
+        let mut hex = Obstacles::new(1);
+        for &t in blocked.iter() {
+            square.block_tile(Position4::from(t));
+            hex.block_tile(Position6Axial::from(t));
+        }
+        for &(t, side) in walls.iter() {
+            let mut sides = [false; 6];
+            sides[side] = true;
+            square.block_borders(&Position4::from(t), &Borders4(sides[0], sides[1], sides[2], sides[3]));
+            hex.block_borders(&Position6Axial::from(t), &Borders6(sides[0], sides[1], sides[2], sides[3], sides[4], sides[5]));
+        }
+        let (a4, b4) = (Position4::from(a), Position4::from(b));
+        let sym = is_visible(&a4, &b4, &square, Vision::Symmetric);
+        prop_assert_eq!(sym, is_visible(&b4, &a4, &square, Vision::Symmetric));
+        prop_assert!(!sym || is_visible(&a4, &b4, &square, Vision::Permissive));
+        let (a6, b6) = (Position6Axial::from(a), Position6Axial::from(b));
+        let sym = is_visible(&a6, &b6, &hex, Vision::Symmetric);
+        prop_assert_eq!(sym, is_visible(&b6, &a6, &hex, Vision::Symmetric));
+        prop_assert!(!sym || is_visible(&a6, &b6, &hex, Vision::Permissive));
+
+        let seen = field_of_view(&a4, 4, &square, Vision::Symmetric);
+        prop_assert!(seen.contains(&a4));
+        for t in seen.iter() {
+            prop_assert!(a4.line_length(t) <= 4.0 + 1e-9);
+            prop_assert!(is_visible(&a4, t, &square, Vision::Symmetric));
+        }
+    }
+
+    #[test]
+    fn open_field_is_fully_visible(x in -100i16..100, y in -100i16..100, radius in 0u32..8) {
+        let p = Position4 { x, y };
+        let open = Obstacles::new(1);
+        prop_assert_eq!(field_of_view(&p, radius, &open, Vision::Symmetric).len(), p.within(radius, Metric4::Chebyshev).filter(|t| p.line_length(t) <= f64::from(radius)).count());
+        let h = Position6Axial { x, y };
+        let all = field_of_view(&h, radius, &Obstacles::new(1), Vision::Symmetric);
+        prop_assert!(h.within(radius).filter(|t| h.line_length(t) <= f64::from(radius)).all(|t| all.contains(&t)));
+    }
+
+    #[test]
+    fn walls_hide_what_is_behind_them(x in -100i16..100, y in -100i16..100) {
+        let p = Position6Axial { x, y };
+        let mut walled = Obstacles::new(1);
+        for (side, n) in p.neighbours().into_iter().enumerate() {
+            let mut sides = [false; 6];
+            sides[side] = true;
+            walled.block_borders(&p, &Borders6(sides[0], sides[1], sides[2], sides[3], sides[4], sides[5]));
+            prop_assert!(!is_visible(&p, &n, &walled, Vision::Permissive));
+        }
+        prop_assert_eq!(field_of_view(&p, 5, &walled, Vision::Permissive).len(), 1);
+        let mut pillar = Obstacles::new(1);
+        let q = Position4 { x, y };
+        pillar.block_tile(Position4 { x: x + 1, y });
+        let (wall, behind) = (Position4::from((x + 1, y)), Position4::from((x + 2, y)));
+        prop_assert!(is_visible(&q, &wall, &pillar, Vision::Symmetric));
+        prop_assert!(!is_visible(&q, &behind, &pillar, Vision::Permissive));
+    }
+}
+
+#[test]
+fn view_radius_is_capped() {
+    let p = Position4 { x: 0i16, y: 0 };
+    let open = Obstacles::new(1);
+    assert_eq!(
+        field_of_view(&p, 1000, &open, Vision::Permissive),
+        field_of_view(&p, MAX_VIEW_RADIUS, &open, Vision::Permissive)
+    );
+}
@@ -0,0 +1,90 @@
+use map_types::*;
+use proptest::prelude::*;
+use structured_digest::{from_canonical_bytes, Digestable};
+
+fn widen(p: Position6Axial) -> Position6Axial<i32> {
+    Position6Axial {
+        x: p.x.into(),
+        y: p.y.into(),
+    }
+}
+
+proptest! {
+    #[test]
+    fn wider_positions_agree(a in any::<(i16, i16)>(), b in any::<(i16, i16)>()) {
+        let (a, b) = (Position4::from(a), Position4::from(b));
+        let (wa, wb) = (Position4::<i64>::from((a.x.into(), a.y.into())), Position4::<i64>::from((b.x.into(), b.y.into())));
+        prop_assert_eq!(wa.field_length(&wb), a.field_length(&b));
+        prop_assert_eq!(wa.line_length(&wb), a.line_length(&b));
+        for metric in [Metric4::Chebyshev, Metric4::Alternating, Metric4::Euclidean] {
+            prop_assert_eq!(metric.distance(&wa, &wb), metric.distance(&a, &b));
+        }
+        let line: Vec<_> = wa.line_to(&wb).map(|p| (p.x, p.y)).collect();
+        let narrow: Vec<_> = a.line_to(&b).map(|p| (i64::from(p.x), i64::from(p.y))).collect();
+        prop_assert_eq!(line, narrow);
+        let (ha, hb) = (Position6Axial::from((a.x, a.y)), Position6Axial::from((b.x, b.y)));
+        prop_assert_eq!(widen(ha).field_length(&widen(hb)), ha.field_length(&hb));
+    }
+
+    #[test]
+    fn wide_maps_leave_the_i16_range(x in 32000i32..33000, y in -100i32..100) {
+        let p = Position6Axial { x, y };
+        prop_assert_eq!(p.checked_neighbours().len(), 6);
+        let q = p + Position6Axial { x: 100, y: 0 };
+        prop_assert_eq!(p.line_to(&q).count(), 101);
+        prop_assert_eq!(p.within(2).count(), 19);
+        let path = astar(&p, &q, &Obstacles::new(1)).unwrap();
+        prop_assert_eq!(path.cost, 100);
+    }
+
+    #[test]
+    fn digests_and_serde_name_the_width(x in any::<i8>(), y in any::<i8>()) {
+        let narrow = Position4 { x: i16::from(x), y: i16::from(y) };
+        let small = Position4 { x, y };
+        let wide = Position4 { x: i32::from(x), y: i32::from(y) };
+        prop_assert_eq!(from_canonical_bytes::<Position4<i8>>(&small.to_canonical_bytes()), Ok(small));
+        prop_assert_eq!(from_canonical_bytes::<Position4<i32>>(&wide.to_canonical_bytes()), Ok(wide));
+        prop_assert!(from_canonical_bytes::<Position4>(&wide.to_canonical_bytes()).is_err());
+        prop_assert!(from_canonical_bytes::<Position4<i32>>(&narrow.to_canonical_bytes()).is_err());
+
+        let json = serde_json::to_value(narrow).unwrap();
+        prop_assert_eq!(&json, &serde_json::json!({"x": x, "y": y}));
+        let json = serde_json::to_value(wide).unwrap();
+        prop_assert_eq!(&json, &serde_json::json!({"x": x, "y": y, "bits": 32}));
+        prop_assert_eq!(serde_json::from_value::<Position4<i32>>(json.clone()).unwrap(), wide);
+        prop_assert!(serde_json::from_value::<Position4>(json).is_err());
+        let cube = Position6Cube::<i64>::from(Position6Axial { x: i64::from(x), y: i64::from(y) });
+        let json = serde_json::to_string(&cube).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Position6Cube<i64>>(&json).unwrap(), cube);
+        prop_assert!(serde_json::from_str::<Position6Cube<i32>>(&json).is_err());
+    }
+}
+
+#[test]
+fn i16_encodings_are_unchanged() {
+    let p = Position4 { x: 1i16, y: -2 };
+    let json = serde_json::to_string(&p).unwrap();
+    assert_eq!(json, r#"{"x":1,"y":-2}"#);
+    assert_eq!(serde_json::from_str::<Position4>(&json).unwrap(), p);
+    assert_eq!(<Position4 as Digestable>::SCHEMA_VERSION, 0);
+    assert_eq!(<Position4<i32> as Digestable>::SCHEMA_VERSION, 32);
+}
+
+#[test]
+fn distances_saturate() {
+    let (a, b) = (Position4 { x: i64::MIN, y: 0 }, Position4 { x: i64::MAX, y: 0 });
+    assert_eq!(a.field_length(&b), u32::MAX);
+    let (a, b) = (Position4 { x: -100i8, y: 0 }, Position4 { x: 100i8, y: 0 });
+    assert_eq!(a.field_length(&b), 200);
+    assert_eq!(a.line_to(&b).count(), 201);
+}
+
+#[test]
+fn wide_hex_lines_count_every_step() {
+    let (a, b) = (Position6Axial { x: 0i64, y: 0 }, Position6Axial { x: 1i64 << 40, y: 0 });
+    let mut line = a.line_to(&b);
+    assert_eq!(line.len(), (1 << 40) + 1);
+    assert_eq!(line.next(), Some(a));
+    assert_eq!(line.next(), Some(Position6Axial { x: 1, y: 0 }));
+    assert_eq!(line.len(), (1 << 40) - 1);
+}
@@ -0,0 +1,105 @@
+use crate::{Digestable, Encoding, Sink};
+use digest::{Digest, Output};
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// An immutable, shared value that remembers its digest.
+///
+/// Like `Nested<D>` it is encoded as the `D` hash of the wrapped value, which is computed once
+/// per encoding and shared between clones. A snapshot holding its parts as `Cached` only hashes
+/// the parts that were replaced since the last time.
+pub struct Cached<T, D: Digest> {
+    node: Arc<Node<T, D>>,
+}
+
+struct Node<T, D: Digest> {
+    value: T,
+    hashes: Mutex<Vec<(Encoding, Output<D>)>>,
+}
+
+impl<T, D: Digest> Cached<T, D> {
+    pub fn new(value: T) -> Self {
+        Cached {
+            node: Arc::new(Node {
+                value,
+                hashes: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// The wrapped value, or a clone of it if it is shared.
+    pub fn into_inner(self) -> T
+    where
+        T: Clone,
+    {
+        match Arc::try_unwrap(self.node) {
+            Ok(node) => node.value,
+            Err(node) => node.value.clone(),
+        }
+    }
+
+    /// Whether both share the same value and therefore the same digests.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.node, &other.node)
+    }
+}
+
+impl<T: Digestable, D: Digest> Cached<T, D> {
+    /// The hash of the wrapped value, what `digest` writes.
+    pub fn hash(&self, encoding: Encoding) -> Output<D> {
+        let mut hashes = self
+            .node
+            .hashes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, hash)) = hashes.iter().find(|(e, _)| *e == encoding) {
+            return hash.clone();
+        }
+        let mut d = D::new();
+        self.node.value.digest(&mut d, encoding);
+        let hash = d.finalize();
+        hashes.push((encoding, hash.clone()));
+        hash
+    }
+}
+
+impl<T: Digestable, D: Digest> Digestable for Cached<T, D> {
+    fn digest<S: Sink + ?Sized>(&self, sink: &mut S, encoding: Encoding) {
+        sink.write(&self.hash(encoding))
+    }
+}
+
+impl<T, D: Digest> Deref for Cached<T, D> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node.value
+    }
+}
+
+impl<T, D: Digest> From<T> for Cached<T, D> {
+    fn from(value: T) -> Self {
+        Cached::new(value)
+    }
+}
+
+// derives would require the digest itself to implement these
+impl<T, D: Digest> Clone for Cached<T, D> {
+    fn clone(&self) -> Self {
+        Cached {
+            node: self.node.clone(),
+        }
+    }
+}
+impl<T: PartialEq, D: Digest> PartialEq for Cached<T, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || **self == **other
+    }
+}
+impl<T: Eq, D: Digest> Eq for Cached<T, D> {}
+impl<T: Debug, D: Digest> Debug for Cached<T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Cached").field(&**self).finish()
+    }
+}
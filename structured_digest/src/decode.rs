@@ -0,0 +1,352 @@
+use crate::{canonical_f32, canonical_f64, Digestable, Encoding, Endian, Version};
+#[cfg(feature = "alloc")]
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+use core::convert::TryFrom;
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DecodeError {
+    UnexpectedEnd,
+    TrailingBytes(usize),
+    InvalidDiscriminant(u32),
+    InvalidUtf8,
+    InvalidChar(u32),
+    Overflow,
+    /// The value decoded, but encoding it again would not give the same bytes.
+    NotCanonical,
+    UnsupportedVersion(Version),
+    /// The input was encoded from a different type.
+    TypeTag,
+    SchemaVersion(u32),
+}
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            DecodeError::TrailingBytes(n) => write!(f, "{} trailing bytes after value", n),
+            DecodeError::InvalidDiscriminant(d) => write!(f, "Invalid discriminant {}", d),
+            DecodeError::InvalidUtf8 => write!(f, "String is not valid UTF-8"),
+            DecodeError::InvalidChar(c) => write!(f, "{:#x} is not a valid char", c),
+            DecodeError::Overflow => write!(f, "Value does not fit the target type"),
+            DecodeError::NotCanonical => write!(f, "Input is not in canonical form"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "Encoding {:?} can not be decoded", v),
+            DecodeError::TypeTag => write!(f, "Type tag does not match"),
+            DecodeError::SchemaVersion(v) => write!(f, "Unsupported schema version {}", v),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl Error for DecodeError {}
+
+/// Cursor over an encoded value. Only encodings from `Version::V1` on are unambiguous
+/// enough to be decoded.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    encoding: Encoding,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8], encoding: Encoding) -> Result<Self, DecodeError> {
+        if encoding.version < Version::V1 {
+            return Err(DecodeError::UnsupportedVersion(encoding.version));
+        }
+        Ok(Reader { bytes, encoding })
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub fn read_len(&mut self) -> Result<usize, DecodeError> {
+        usize::try_from(u64::decode(self)?).map_err(|_| DecodeError::Overflow)
+    }
+}
+
+pub trait Decode: Sized {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError>;
+}
+
+/// Reads and checks what `digest_type_tag` wrote for `T`.
+pub fn decode_type_tag<T: Digestable + ?Sized>(reader: &mut Reader<'_>) -> Result<(), DecodeError> {
+    if let Some(tag) = T::TYPE_TAG {
+        if reader.encoding().version >= Version::V2 {
+            let len = reader.read_len()?;
+            if reader.read_bytes(len)? != tag.as_bytes() {
+                return Err(DecodeError::TypeTag);
+            }
+            let version = u32::decode(reader)?;
+            if version != T::SCHEMA_VERSION {
+                return Err(DecodeError::SchemaVersion(version));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a value that has to span all of `bytes`.
+pub fn from_bytes<T: Decode>(bytes: &[u8], encoding: Encoding) -> Result<T, DecodeError> {
+    let mut reader = Reader::new(bytes, encoding)?;
+    let value = T::decode(&mut reader)?;
+    match reader.remaining().len() {
+        0 => Ok(value),
+        n => Err(DecodeError::TrailingBytes(n)),
+    }
+}
+
+/// Inverse of `Digestable::to_canonical_bytes`.
+pub fn from_canonical_bytes<T: Decode>(bytes: &[u8]) -> Result<T, DecodeError> {
+    from_bytes(bytes, Encoding::CANONICAL)
+}
+
+macro_rules! int_impl {
+    ($($t:ty)*) => {$(
+        impl Decode for $t {
+            fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+                let mut bytes = [0; core::mem::size_of::<$t>()];
+                let len = bytes.len();
+                bytes.copy_from_slice(reader.read_bytes(len)?);
+                Ok(match reader.encoding().endian {
+                    Endian::Little => <$t>::from_le_bytes(bytes),
+                    Endian::Big => <$t>::from_be_bytes(bytes),
+                })
+            }
+        }
+    )*};
+}
+int_impl!(i8 i16 i32 i64 i128 u8 u16 u32 u64 u128);
+
+impl Decode for usize {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        usize::try_from(u64::decode(reader)?).map_err(|_| DecodeError::Overflow)
+    }
+}
+impl Decode for isize {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        isize::try_from(i64::decode(reader)?).map_err(|_| DecodeError::Overflow)
+    }
+}
+impl Decode for bool {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        match u8::decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            d => Err(DecodeError::InvalidDiscriminant(d.into())),
+        }
+    }
+}
+impl Decode for char {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let c = u32::decode(reader)?;
+        core::char::from_u32(c).ok_or(DecodeError::InvalidChar(c))
+    }
+}
+impl Decode for f32 {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let f = f32::from_bits(u32::decode(reader)?);
+        if canonical_f32(f) == f.to_bits() {
+            Ok(f)
+        } else {
+            Err(DecodeError::NotCanonical)
+        }
+    }
+}
+impl Decode for f64 {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let f = f64::from_bits(u64::decode(reader)?);
+        if canonical_f64(f) == f.to_bits() {
+            Ok(f)
+        } else {
+            Err(DecodeError::NotCanonical)
+        }
+    }
+}
+impl<T: Decode> Decode for Option<T> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        match u8::decode(reader)? {
+            0 => Ok(None),
+            1 => T::decode(reader).map(Some),
+            d => Err(DecodeError::InvalidDiscriminant(d.into())),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let len = reader.read_len()?;
+        // every element has to take at least one byte, or a corrupt prefix could loop for as
+        // long as it claims; vectors of values that encode to no bytes can't be decoded
+        if len > reader.remaining().len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let mut v = Vec::with_capacity(len);
+        for _ in 0..len {
+            v.push(T::decode(reader)?);
+        }
+        Ok(v)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let mut v = Vec::with_capacity(N);
+        for _ in 0..N {
+            v.push(T::decode(reader)?);
+        }
+        Ok(<[T; N]>::try_from(v).ok().expect("exactly N elements"))
+    }
+}
+#[cfg(feature = "alloc")]
+impl Decode for String {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let len = reader.read_len()?;
+        let bytes = reader.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Decode> Decode for Box<T> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        T::decode(reader).map(Box::new)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Decode> Decode for Rc<T> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        T::decode(reader).map(Rc::new)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Decode> Decode for Arc<T> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        T::decode(reader).map(Arc::new)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<B: ToOwned + ?Sized> Decode for Cow<'_, B>
+where
+    B::Owned: Decode,
+{
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        B::Owned::decode(reader).map(Cow::Owned)
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($name:ident)+) => {
+        impl<$($name: Decode),+> Decode for ($($name,)+) {
+            fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+                Ok(($($name::decode(reader)?,)+))
+            }
+        }
+    };
+}
+tuple_impl!(A);
+tuple_impl!(A B);
+tuple_impl!(A B C);
+tuple_impl!(A B C D);
+tuple_impl!(A B C D E);
+tuple_impl!(A B C D E F);
+tuple_impl!(A B C D E F G);
+tuple_impl!(A B C D E F G H);
+tuple_impl!(A B C D E F G H I);
+tuple_impl!(A B C D E F G H I J);
+tuple_impl!(A B C D E F G H I J K);
+tuple_impl!(A B C D E F G H I J K L);
+
+#[cfg(feature = "alloc")]
+impl<K: Decode + Ord, V: Decode> Decode for BTreeMap<K, V> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let mut map = BTreeMap::new();
+        for _ in 0..reader.read_len()? {
+            let (k, v) = <(K, V)>::decode(reader)?;
+            if map.keys().next_back().is_some_and(|last| *last >= k) {
+                return Err(DecodeError::NotCanonical);
+            }
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Decode + Ord> Decode for BTreeSet<T> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let mut set = BTreeSet::new();
+        for _ in 0..reader.read_len()? {
+            let x = T::decode(reader)?;
+            if set.iter().next_back().is_some_and(|last| *last >= x) {
+                return Err(DecodeError::NotCanonical);
+            }
+            set.insert(x);
+        }
+        Ok(set)
+    }
+}
+
+/// Decodes the elements written by `digest_unordered`, which have to be sorted by their encoding.
+#[cfg(feature = "std")]
+fn decode_unordered<T: Decode>(
+    reader: &mut Reader<'_>,
+    mut insert: impl FnMut(T),
+) -> Result<(), DecodeError> {
+    let mut last: Option<&[u8]> = None;
+    for _ in 0..reader.read_len()? {
+        let start = reader.remaining();
+        let x = T::decode(reader)?;
+        let encoded = &start[..start.len() - reader.remaining().len()];
+        if last.is_some_and(|last| last >= encoded) {
+            return Err(DecodeError::NotCanonical);
+        }
+        last = Some(encoded);
+        insert(x);
+    }
+    Ok(())
+}
+#[cfg(feature = "std")]
+impl<K: Decode + Eq + Hash, V: Decode, H: BuildHasher + Default> Decode for HashMap<K, V, H> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let mut map = HashMap::default();
+        decode_unordered(reader, |(k, v)| {
+            map.insert(k, v);
+        })?;
+        Ok(map)
+    }
+}
+#[cfg(feature = "std")]
+impl<T: Decode + Eq + Hash, H: BuildHasher + Default> Decode for HashSet<T, H> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let mut set = HashSet::default();
+        decode_unordered(reader, |x| {
+            set.insert(x);
+        })?;
+        Ok(set)
+    }
+}
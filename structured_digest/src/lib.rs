@@ -1,165 +1,411 @@
-#![feature(num_as_ne_bytes,min_const_generics)]
+#![cfg_attr(not(feature="std"),no_std)]
+#[cfg(feature="alloc")]
+extern crate alloc;
+
+use core::marker::PhantomData;
 use digest::Digest;
+#[cfg(feature="alloc")]
+use alloc::{borrow::{Cow, ToOwned}, boxed::Box, collections::{BTreeMap, BTreeSet}, rc::Rc, string::String, sync::Arc, vec::Vec};
+#[cfg(feature="alloc")]
+use digest::Output;
+#[cfg(feature="std")]
+use std::collections::{HashMap, HashSet};
 
-pub trait Digestable{
-    fn update_le<D:Digest>(&self,digest:&mut D);
-    fn update_be<D:Digest>(&self,digest:&mut D);
+pub use digest;
+pub use structured_digest_derive::{Decode, Digestable};
+
+pub mod sink;
+
+pub use sink::*;
+
+pub mod decode;
+
+pub use decode::*;
+
+#[cfg(feature="alloc")]
+pub mod merkle;
+
+#[cfg(feature="alloc")]
+pub use merkle::{MerkleProof, Side};
+
+#[cfg(feature="std")]
+pub mod cached;
+
+#[cfg(feature="std")]
+pub use cached::Cached;
+
+#[doc(hidden)]
+#[cfg(feature="alloc")]
+pub mod __private{
+    pub use alloc::vec::Vec;
 }
 
-impl Digestable for i8{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
-    }
+/// Expands to its input only if this crate has allocation support, used by the derive.
+#[doc(hidden)]
+#[cfg(feature="alloc")]
+#[macro_export]
+macro_rules! __if_alloc {
+    ($($t:tt)*) => { $($t)* };
+}
+#[doc(hidden)]
+#[cfg(not(feature="alloc"))]
+#[macro_export]
+macro_rules! __if_alloc {
+    ($($t:tt)*) => {};
+}
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
-    }
+/// Encoding revisions. Digests of older versions stay reproducible so ids created with them
+/// can still be verified.
+#[derive(Debug,Copy,Clone,Eq,PartialEq,Ord,PartialOrd,Hash)]
+pub enum Version{
+    /// Plain concatenation of all fields. Ambiguous for strings and collections.
+    V0,
+    /// Strings, byte slices and vectors are prefixed with their length as `u64`.
+    V1,
+    /// Like `V1`, types with a `Digestable::TYPE_TAG` are prefixed with it and their schema version.
+    V2,
 }
-impl Digestable for i16{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
-    }
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
-    }
+impl Version{
+    pub const LATEST:Version=Version::V2;
 }
-impl Digestable for i32{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+
+#[derive(Debug,Copy,Clone,Eq,PartialEq,Hash)]
+pub enum Endian{
+    Little,
+    Big,
+}
+
+#[derive(Debug,Copy,Clone,Eq,PartialEq,Hash)]
+pub struct Encoding{
+    pub endian:Endian,
+    pub version:Version,
+}
+
+impl Encoding{
+    /// The encoding `to_canonical_bytes` produces and `from_canonical_bytes` expects.
+    pub const CANONICAL:Encoding=Encoding::le(Version::LATEST);
+
+    pub const fn le(version:Version)->Self{
+        Encoding{endian:Endian::Little,version}
     }
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+    pub const fn be(version:Version)->Self{
+        Encoding{endian:Endian::Big,version}
     }
 }
-impl Digestable for i64{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+
+pub trait Digestable{
+    /// Binds the digest to the type from `Version::V2` on, independent of its Rust name.
+    /// Derived impls set it with `#[digest(tag = "...")]` and have none without it.
+    const TYPE_TAG:Option<&'static str>=None;
+    /// Bumped when the encoded fields change, set with `#[digest(version = n)]`.
+    const SCHEMA_VERSION:u32=0;
+    /// Fields listed by `merkle_children` after the type tag, set by the derive for structs.
+    const FIELDS:&'static [&'static str]=&[];
+
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding);
+
+    fn update_le<S:Sink+?Sized>(&self,sink:&mut S,version:Version){
+        self.digest(sink,Encoding::le(version))
     }
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+    fn update_be<S:Sink+?Sized>(&self,sink:&mut S,version:Version){
+        self.digest(sink,Encoding::be(version))
     }
-}
-impl Digestable for i128{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+
+    #[cfg(feature="alloc")]
+    fn to_canonical_bytes(&self)->Vec<u8>{
+        let mut bytes=ByteSink::default();
+        self.digest(&mut bytes,Encoding::CANONICAL);
+        bytes.0
     }
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+    /// Subtree hashes in Merkle mode, `None` if the value is a single leaf.
+    /// Collections list their elements and derived structs their fields.
+    #[cfg(feature="alloc")]
+    fn merkle_children<D:Digest>(&self,_encoding:Encoding)->Option<Vec<Output<D>>>{
+        None
     }
-}
-impl Digestable for u8{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+
+    #[cfg(feature="alloc")]
+    fn merkle_root<D:Digest>(&self,encoding:Encoding)->Output<D>{
+        match self.merkle_children::<D>(encoding) {
+            Some(children)=>merkle::root::<D>(&children),
+            None=>merkle::leaf_hash::<D,_>(self,encoding),
+        }
     }
+}
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+/// Writes the type tag and schema version of `T` if `encoding` includes them.
+pub fn digest_type_tag<T:Digestable+?Sized,S:Sink+?Sized>(sink:&mut S,encoding:Encoding){
+    if let Some(tag)=T::TYPE_TAG {
+        if encoding.version>=Version::V2 {
+            tag.digest(sink,encoding);
+            T::SCHEMA_VERSION.digest(sink,encoding);
+        }
     }
 }
-impl Digestable for u16{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+
+fn digest_len<S:Sink+?Sized>(len:usize,sink:&mut S,encoding:Encoding){
+    if encoding.version>=Version::V1 {
+        (len as u64).digest(sink,encoding)
     }
+}
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+macro_rules! int_impl {
+    ($($t:ty)*) => {$(
+        impl Digestable for $t{
+            fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+                match encoding.endian {
+                    Endian::Little=>sink.write(&self.to_le_bytes()),
+                    Endian::Big=>sink.write(&self.to_be_bytes()),
+                }
+            }
+        }
+    )*};
+}
+int_impl!(i8 i16 i32 i64 i128 u8 u16 u32 u64 u128);
+
+/// Hashed as `u64` so 32 and 64 bit platforms agree.
+impl Digestable for usize{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (*self as u64).digest(sink,encoding)
     }
 }
-impl Digestable for u32{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+/// Hashed as `i64` so 32 and 64 bit platforms agree.
+impl Digestable for isize{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (*self as i64).digest(sink,encoding)
     }
-
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+}
+impl Digestable for bool{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (*self as u8).digest(sink,encoding)
     }
 }
-impl Digestable for u64{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+impl Digestable for char{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (*self as u32).digest(sink,encoding)
     }
+}
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+/// All NaNs hash like the canonical quiet NaN and `-0.0` hashes like `0.0`.
+fn canonical_f32(f:f32)->u32{
+    if f.is_nan() {
+        0x7fc0_0000
+    } else if f==0.0 {
+        0
+    } else {
+        f.to_bits()
     }
 }
-impl Digestable for u128{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_le_bytes())
+fn canonical_f64(f:f64)->u64{
+    if f.is_nan() {
+        0x7ff8_0000_0000_0000
+    } else if f==0.0 {
+        0
+    } else {
+        f.to_bits()
     }
-
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.to_be_bytes())
+}
+impl Digestable for f32{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        canonical_f32(*self).digest(sink,encoding)
+    }
+}
+impl Digestable for f64{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        canonical_f64(*self).digest(sink,encoding)
     }
 }
 impl<T:Digestable> Digestable for Option<T>{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
         match self {
-            None=>{(0 as u8).update_le(digest);},
+            None=>0u8.digest(sink,encoding),
             Some(x)=>{
-                (1 as u8).update_le(digest);
-                x.update_le(digest);
+                1u8.digest(sink,encoding);
+                x.digest(sink,encoding);
             }
         }
     }
+}
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        match self {
-            None=>(0 as u8).update_be(digest),
-            Some(x)=>{
-                (1 as u8).update_be(digest);
-                x.update_be(digest);
-            }
-        }
+impl<T:Digestable> Digestable for [T]{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        digest_len(self.len(),sink,encoding);
+        self.iter().for_each(|x|x.digest(sink,encoding))
     }
-}
 
+    #[cfg(feature="alloc")]
+    fn merkle_children<D:Digest>(&self,encoding:Encoding)->Option<Vec<Output<D>>>{
+        Some(self.iter().map(|x|x.merkle_root::<D>(encoding)).collect())
+    }
+}
+#[cfg(feature="alloc")]
 impl<T:Digestable> Digestable for Vec<T>{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        self.iter().for_each(|x|x.update_le(digest))
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        self.as_slice().digest(sink,encoding)
     }
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        self.iter().for_each(|x|x.update_be(digest))
+    fn merkle_children<D:Digest>(&self,encoding:Encoding)->Option<Vec<Output<D>>>{
+        self.as_slice().merkle_children::<D>(encoding)
     }
 }
 impl<T:Digestable,const N:usize> Digestable for [T;N]{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        self.iter().for_each(|x|x.update_le(digest))
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        self.iter().for_each(|x|x.digest(sink,encoding))
     }
+}
+impl Digestable for str{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        digest_len(self.len(),sink,encoding);
+        sink.write(self.as_bytes())
+    }
+}
+#[cfg(feature="alloc")]
+impl Digestable for String{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        self.as_str().digest(sink,encoding)
+    }
+}
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        self.iter().for_each(|x|x.update_be(digest))
+impl<T:Digestable+?Sized> Digestable for &T{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (**self).digest(sink,encoding)
+    }
+
+    #[cfg(feature="alloc")]
+    fn merkle_children<D:Digest>(&self,encoding:Encoding)->Option<Vec<Output<D>>>{
+        (**self).merkle_children::<D>(encoding)
     }
 }
-impl Digestable for &str{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.as_bytes())
+#[cfg(feature="alloc")]
+impl<T:Digestable+?Sized> Digestable for Box<T>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (**self).digest(sink,encoding)
     }
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self.as_bytes())
+    fn merkle_children<D:Digest>(&self,encoding:Encoding)->Option<Vec<Output<D>>>{
+        (**self).merkle_children::<D>(encoding)
     }
 }
-impl Digestable for &[u8]{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        digest.update(self)
+#[cfg(feature="alloc")]
+impl<T:Digestable+?Sized> Digestable for Rc<T>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (**self).digest(sink,encoding)
     }
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        digest.update(self)
+    fn merkle_children<D:Digest>(&self,encoding:Encoding)->Option<Vec<Output<D>>>{
+        (**self).merkle_children::<D>(encoding)
     }
 }
-impl Digestable for String{
-    fn update_le<D:Digest>(&self,digest:&mut D) {
-        self.as_str().update_le(digest)
+#[cfg(feature="alloc")]
+impl<T:Digestable+?Sized> Digestable for Arc<T>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (**self).digest(sink,encoding)
+    }
+
+    fn merkle_children<D:Digest>(&self,encoding:Encoding)->Option<Vec<Output<D>>>{
+        (**self).merkle_children::<D>(encoding)
     }
+}
+#[cfg(feature="alloc")]
+impl<B:Digestable+ToOwned+?Sized> Digestable for Cow<'_,B>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        (**self).digest(sink,encoding)
+    }
+
+    fn merkle_children<D:Digest>(&self,encoding:Encoding)->Option<Vec<Output<D>>>{
+        (**self).merkle_children::<D>(encoding)
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($name:ident)+) => {
+        impl<$($name:Digestable),+> Digestable for ($($name,)+){
+            #[allow(non_snake_case)]
+            fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+                let ($($name,)+)=self;
+                $($name.digest(sink,encoding);)+
+            }
+        }
+    };
+}
+tuple_impl!(A);
+tuple_impl!(A B);
+tuple_impl!(A B C);
+tuple_impl!(A B C D);
+tuple_impl!(A B C D E);
+tuple_impl!(A B C D E F);
+tuple_impl!(A B C D E F G);
+tuple_impl!(A B C D E F G H);
+tuple_impl!(A B C D E F G H I);
+tuple_impl!(A B C D E F G H I J);
+tuple_impl!(A B C D E F G H I J K);
+tuple_impl!(A B C D E F G H I J K L);
+
+#[cfg(feature="alloc")]
+impl<K:Digestable,V:Digestable> Digestable for BTreeMap<K,V>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        digest_len(self.len(),sink,encoding);
+        self.iter().for_each(|x|x.digest(sink,encoding))
+    }
+}
+#[cfg(feature="alloc")]
+impl<T:Digestable> Digestable for BTreeSet<T>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        digest_len(self.len(),sink,encoding);
+        self.iter().for_each(|x|x.digest(sink,encoding))
+    }
+}
+
+/// Encodes every element on its own and writes the sorted encodings,
+/// so the result does not depend on iteration order.
+#[cfg(feature="std")]
+fn digest_unordered<S:Sink+?Sized,T:Digestable,I:ExactSizeIterator<Item=T>>(iter:I,sink:&mut S,encoding:Encoding){
+    digest_len(iter.len(),sink,encoding);
+    let mut encoded:Vec<_>=iter.map(|x|{
+        let mut bytes=ByteSink::default();
+        x.digest(&mut bytes,encoding);
+        bytes.0
+    }).collect();
+    encoded.sort_unstable();
+    encoded.iter().for_each(|x|sink.write(x))
+}
+#[cfg(feature="std")]
+impl<K:Digestable,V:Digestable,H> Digestable for HashMap<K,V,H>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        digest_unordered(self.iter(),sink,encoding)
+    }
+}
+#[cfg(feature="std")]
+impl<T:Digestable,H> Digestable for HashSet<T,H>{
+    fn digest<S:Sink+?Sized>(&self,sink:&mut S,encoding:Encoding) {
+        digest_unordered(self.iter(),sink,encoding)
+    }
+}
+
+/// For use with `#[digest(with = structured_digest::Nested::<D>)]`:
+/// hashes the field with a fresh `D` and writes only the result.
+/// The field can't be decoded again, so there is no `Nested::decode` and deriving `Decode` fails:
+///
+/// ```compile_fail
+/// use sha2::Sha256;
+/// use structured_digest::{Decode, Digestable, Nested};
+///
+/// #[derive(Digestable, Decode)]
+/// struct Snapshot {
+///     #[digest(with = Nested::<Sha256>)]
+///     parts: Vec<u8>,
+/// }
+/// ```
+pub struct Nested<D:Digest>(PhantomData<D>);
 
-    fn update_be<D:Digest>(&self,digest:&mut D) {
-        self.as_str().update_be(digest)
+impl<D:Digest> Nested<D>{
+    pub fn digest<T:Digestable+?Sized,S:Sink+?Sized>(value:&T,sink:&mut S,encoding:Encoding) {
+        let mut d=D::new();
+        value.digest(&mut d,encoding);
+        sink.write(&d.finalize())
     }
 }
@@ -0,0 +1,220 @@
+use crate::{digest_type_tag, Decode, DecodeError, Digestable, Encoding, Reader, Sink, Version};
+use alloc::{vec, vec::Vec};
+use digest::{Digest, Output};
+
+/// Leaves and inner nodes are hashed with distinct prefixes like in RFC 6962,
+/// so a leaf can never be passed off as a subtree. Every collection closes its tree
+/// with a third prefix and its length, so nesting and element counts are part of the root.
+const LEAF: u8 = 0;
+const NODE: u8 = 1;
+const LIST: u8 = 2;
+
+/// A digest with the leaf prefix already written, for leaves that are not a single `Digestable`.
+pub fn leaf_hasher<D: Digest>() -> D {
+    let mut d = D::new();
+    d.update([LEAF]);
+    d
+}
+
+pub fn leaf_hash<D: Digest, T: Digestable + ?Sized>(value: &T, encoding: Encoding) -> Output<D> {
+    let mut d = leaf_hasher::<D>();
+    value.digest(&mut d, encoding);
+    d.finalize()
+}
+
+/// Leaf for the type tag of `T`, derived types list it before their fields.
+pub fn type_tag_leaf<T: Digestable + ?Sized, D: Digest>(encoding: Encoding) -> Option<Output<D>> {
+    if T::TYPE_TAG.is_none() || encoding.version < Version::V2 {
+        return None;
+    }
+    let mut d = leaf_hasher::<D>();
+    digest_type_tag::<T, _>(&mut d, encoding);
+    Some(d.finalize())
+}
+
+pub fn node_hash<D: Digest>(left: &Output<D>, right: &Output<D>) -> Output<D> {
+    let mut d = D::new();
+    d.update([NODE]);
+    d.update(left);
+    d.update(right);
+    d.finalize()
+}
+
+/// Size of the left subtree: the largest power of two below `len`.
+fn split(len: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < len {
+        k *= 2;
+    }
+    k
+}
+
+/// Hash of a collection with `len` children whose tree has the root `tree`, empty if there are none.
+pub fn list_hash<D: Digest>(len: u64, tree: &[u8]) -> Output<D> {
+    let mut d = D::new();
+    d.update([LIST]);
+    d.update(len.to_le_bytes());
+    d.update(tree);
+    d.finalize()
+}
+
+fn tree<D: Digest>(hashes: &[Output<D>]) -> Output<D> {
+    match hashes.len() {
+        1 => hashes[0].clone(),
+        n => {
+            let (left, right) = hashes.split_at(split(n));
+            node_hash::<D>(&tree::<D>(left), &tree::<D>(right))
+        }
+    }
+}
+
+/// Root of a collection over already hashed children.
+pub fn root<D: Digest>(hashes: &[Output<D>]) -> Output<D> {
+    if hashes.is_empty() {
+        return list_hash::<D>(0, &[]);
+    }
+    list_hash::<D>(hashes.len() as u64, &tree::<D>(hashes))
+}
+
+/// Proves that `hashes[index]` is part of `root(hashes)`.
+pub fn prove<D: Digest>(hashes: &[Output<D>], index: usize) -> Option<MerkleProof<D>> {
+    fn build<D: Digest>(hashes: &[Output<D>], index: usize, path: &mut Path<D>) {
+        if hashes.len() <= 1 {
+            return;
+        }
+        let (left, right) = hashes.split_at(split(hashes.len()));
+        if index < left.len() {
+            build::<D>(left, index, path);
+            path.push((Side::Right, tree::<D>(right)));
+        } else {
+            build::<D>(right, index - left.len(), path);
+            path.push((Side::Left, tree::<D>(left)));
+        }
+    }
+    if index >= hashes.len() {
+        return None;
+    }
+    let mut path = Vec::new();
+    build::<D>(hashes, index, &mut path);
+    Some(MerkleProof {
+        levels: vec![(hashes.len() as u64, path)],
+    })
+}
+
+/// Proves that child `index` of `value`, as listed by `Digestable::merkle_children`,
+/// is part of `value.merkle_root`.
+pub fn prove_child<D: Digest, T: Digestable + ?Sized>(
+    value: &T,
+    index: usize,
+    encoding: Encoding,
+) -> Option<MerkleProof<D>> {
+    prove::<D>(&value.merkle_children::<D>(encoding)?, index)
+}
+
+/// Proves that the field `name` of a derived struct is part of `value.merkle_root`.
+pub fn prove_field<D: Digest, T: Digestable + ?Sized>(
+    value: &T,
+    name: &str,
+    encoding: Encoding,
+) -> Option<MerkleProof<D>> {
+    let field = T::FIELDS.iter().position(|f| *f == name)?;
+    let tag = T::TYPE_TAG.is_some() && encoding.version >= Version::V2;
+    prove_child::<D, T>(value, field + tag as usize, encoding)
+}
+
+/// The side of the sibling hash at one level of a proof.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Sibling hashes within one collection, from a child up to the top of its tree.
+pub type Path<D> = Vec<(Side, Output<D>)>;
+
+/// Sibling hashes from a subtree up to the root. There is one level per enclosing collection,
+/// with its number of children and the siblings within it.
+pub struct MerkleProof<D: Digest> {
+    pub levels: Vec<(u64, Path<D>)>,
+}
+
+// derives would require the digest itself to implement these
+impl<D: Digest> Clone for MerkleProof<D> {
+    fn clone(&self) -> Self {
+        MerkleProof {
+            levels: self.levels.clone(),
+        }
+    }
+}
+impl<D: Digest> PartialEq for MerkleProof<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.levels == other.levels
+    }
+}
+impl<D: Digest> Eq for MerkleProof<D> {}
+impl<D: Digest> core::fmt::Debug for MerkleProof<D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MerkleProof").field("levels", &self.levels).finish()
+    }
+}
+
+impl<D: Digest> MerkleProof<D> {
+    /// The root a subtree with hash `subtree` is part of, if this proof is correct.
+    pub fn root(&self, subtree: Output<D>) -> Output<D> {
+        self.levels.iter().fold(subtree, |acc, (len, path)| {
+            let tree = path.iter().fold(acc, |acc, (side, sibling)| match side {
+                Side::Left => node_hash::<D>(sibling, &acc),
+                Side::Right => node_hash::<D>(&acc, sibling),
+            });
+            list_hash::<D>(*len, &tree)
+        })
+    }
+
+    pub fn verify(&self, subtree: Output<D>, root: &[u8]) -> bool {
+        self.root(subtree)[..] == *root
+    }
+
+    /// Continues the proof from the root of this tree into a tree containing it.
+    pub fn then(mut self, outer: MerkleProof<D>) -> Self {
+        self.levels.extend(outer.levels);
+        self
+    }
+}
+
+impl<D: Digest> Digestable for MerkleProof<D> {
+    fn digest<S: Sink + ?Sized>(&self, sink: &mut S, encoding: Encoding) {
+        (self.levels.len() as u64).digest(sink, encoding);
+        for (len, path) in self.levels.iter() {
+            len.digest(sink, encoding);
+            (path.len() as u64).digest(sink, encoding);
+            for (side, hash) in path.iter() {
+                (*side == Side::Right).digest(sink, encoding);
+                sink.write(hash);
+            }
+        }
+    }
+}
+
+impl<D: Digest> Decode for MerkleProof<D> {
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        let count = reader.read_len()?;
+        let mut levels = Vec::with_capacity(count.min(reader.remaining().len()));
+        for _ in 0..count {
+            let len = u64::decode(reader)?;
+            let steps = reader.read_len()?;
+            let mut path = Vec::with_capacity(steps.min(reader.remaining().len()));
+            for _ in 0..steps {
+                let side = if bool::decode(reader)? {
+                    Side::Right
+                } else {
+                    Side::Left
+                };
+                let mut hash = Output::<D>::default();
+                hash.copy_from_slice(reader.read_bytes(D::output_size())?);
+                path.push((side, hash));
+            }
+            levels.push((len, path));
+        }
+        Ok(MerkleProof { levels })
+    }
+}
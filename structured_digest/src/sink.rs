@@ -0,0 +1,36 @@
+use core::hash::Hasher;
+use digest::Digest;
+#[cfg(feature="alloc")]
+use alloc::vec::Vec;
+
+/// Receives the byte stream produced by `Digestable::digest`.
+pub trait Sink{
+    fn write(&mut self,data:&[u8]);
+}
+
+impl<D:Digest> Sink for D{
+    fn write(&mut self, data: &[u8]) {
+        Digest::update(self,data)
+    }
+}
+
+/// Collects the encoding instead of hashing it.
+#[cfg(feature="alloc")]
+#[derive(Debug,Default,Clone,Eq,PartialEq)]
+pub struct ByteSink(pub Vec<u8>);
+
+#[cfg(feature="alloc")]
+impl Sink for ByteSink{
+    fn write(&mut self, data: &[u8]) {
+        self.0.extend_from_slice(data)
+    }
+}
+
+/// Feeds the encoding into a `core::hash::Hasher`, for implementing `Hash` through `Digestable`.
+pub struct HasherSink<'a,H:Hasher>(pub &'a mut H);
+
+impl<H:Hasher> Sink for HasherSink<'_,H>{
+    fn write(&mut self, data: &[u8]) {
+        self.0.write(data)
+    }
+}
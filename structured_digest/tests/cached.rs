@@ -0,0 +1,40 @@
+#![cfg(feature = "std")]
+
+use sha2::Sha256;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use structured_digest::*;
+
+static DIGESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts how often it is hashed.
+struct Counted(u32);
+
+impl Digestable for Counted {
+    fn digest<S: Sink + ?Sized>(&self, sink: &mut S, encoding: Encoding) {
+        DIGESTS.fetch_add(1, Ordering::SeqCst);
+        self.0.digest(sink, encoding)
+    }
+}
+
+#[test]
+fn cached_hashes_like_nested_once() {
+    let v1 = Encoding::le(Version::V1);
+    let mut nested = ByteSink::default();
+    Nested::<Sha256>::digest(&Counted(7), &mut nested, v1);
+
+    let cached = Cached::<_, Sha256>::new(Counted(7));
+    let copy = cached.clone();
+    assert!(cached.ptr_eq(&copy));
+    let before = DIGESTS.load(Ordering::SeqCst);
+    let mut first = ByteSink::default();
+    cached.digest(&mut first, v1);
+    let mut second = ByteSink::default();
+    copy.digest(&mut second, v1);
+    assert_eq!(first.0, nested.0);
+    assert_eq!(second.0, nested.0);
+    assert_eq!(DIGESTS.load(Ordering::SeqCst), before + 1);
+
+    assert_ne!(cached.hash(Encoding::be(Version::V1)), cached.hash(v1));
+    assert_eq!(DIGESTS.load(Ordering::SeqCst), before + 2);
+    assert!(!cached.ptr_eq(&Cached::new(Counted(7))));
+}
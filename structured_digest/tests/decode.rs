@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use structured_digest::*;
+
+#[derive(Debug, PartialEq, Digestable, Decode)]
+struct Room {
+    name: String,
+    doors: Vec<Door>,
+    lit: Option<bool>,
+    marks: BTreeMap<u8, char>,
+}
+
+#[derive(Debug, PartialEq, Digestable, Decode)]
+enum Door {
+    Open,
+    Locked(u32),
+    Hidden { depth: u8 },
+}
+
+#[test]
+fn canonical_bytes_round_trip() {
+    let room = Room {
+        name: "hall".into(),
+        doors: vec![Door::Open, Door::Locked(7), Door::Hidden { depth: 2 }],
+        lit: Some(false),
+        marks: vec![(1, 'x'), (2, 'ÿ')].into_iter().collect(),
+    };
+    let bytes = room.to_canonical_bytes();
+    assert_eq!(from_canonical_bytes::<Room>(&bytes), Ok(room));
+    let be = Encoding::be(Version::V1);
+    let mut sink = ByteSink::default();
+    Door::Locked(7).digest(&mut sink, be);
+    assert_eq!(from_bytes::<Door>(&sink.0, be), Ok(Door::Locked(7)));
+}
+
+#[test]
+fn only_canonical_bytes_decode() {
+    let mut bytes = Door::Open.to_canonical_bytes();
+    bytes.push(0);
+    assert_eq!(
+        from_canonical_bytes::<Door>(&bytes),
+        Err(DecodeError::TrailingBytes(1))
+    );
+    assert_eq!(
+        from_bytes::<Door>(&[3, 0, 0, 0], Encoding::le(Version::V1)),
+        Err(DecodeError::InvalidDiscriminant(3))
+    );
+    let unsorted = vec![(2u8, 'a'), (1u8, 'b')].to_canonical_bytes();
+    assert_eq!(
+        from_canonical_bytes::<BTreeMap<u8, char>>(&unsorted),
+        Err(DecodeError::NotCanonical)
+    );
+    assert_eq!(
+        from_bytes::<u8>(&[0], Encoding::le(Version::V0)),
+        Err(DecodeError::UnsupportedVersion(Version::V0))
+    );
+}
+
+#[test]
+fn lengths_are_bounded_by_the_input() {
+    let huge = u64::MAX.to_le_bytes();
+    assert_eq!(
+        from_canonical_bytes::<Vec<[u8; 0]>>(&huge),
+        Err(DecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        from_canonical_bytes::<Vec<u8>>(&huge),
+        Err(DecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        from_canonical_bytes::<Vec<[u8; 0]>>(&0u64.to_le_bytes()),
+        Ok(vec![])
+    );
+    let bytes = vec![1u16, 2].to_canonical_bytes();
+    assert_eq!(from_canonical_bytes::<Vec<u16>>(&bytes), Ok(vec![1, 2]));
+}
@@ -0,0 +1,87 @@
+use sha2::{Digest, Sha256};
+use structured_digest::*;
+
+fn bytes<T: Digestable + ?Sized>(value: &T, encoding: Encoding) -> Vec<u8> {
+    let mut sink = ByteSink::default();
+    value.digest(&mut sink, encoding);
+    sink.0
+}
+
+#[test]
+fn length_prefixes_keep_strings_apart() {
+    let (a, b) = (vec!["ab", "c"], vec!["a", "bc"]);
+    let (v0, v1) = (Encoding::le(Version::V0), Encoding::le(Version::V1));
+    assert_eq!(bytes(&a, v0), bytes(&b, v0));
+    for version in [Version::V1, Version::V2] {
+        let encoding = Encoding::le(version);
+        assert_ne!(bytes(&a, encoding), bytes(&b, encoding));
+    }
+    assert_eq!(bytes("ab", v1), vec![2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+    assert_ne!(
+        bytes(&vec![vec![1u8], vec![]], v1),
+        bytes(&vec![vec![], vec![1u8]], v1)
+    );
+}
+
+#[test]
+fn equal_floats_encode_alike() {
+    let v1 = Encoding::le(Version::V1);
+    assert_eq!(bytes(&-0.0f32, v1), bytes(&0.0f32, v1));
+    assert_eq!(bytes(&-0.0f64, v1), bytes(&0.0f64, v1));
+    let other_nan = f64::from_bits(f64::NAN.to_bits() | 1);
+    assert!(other_nan.is_nan());
+    assert_eq!(bytes(&other_nan, v1), bytes(&f64::NAN, v1));
+    assert_eq!(bytes(&-f32::NAN, v1), bytes(&f32::NAN, v1));
+    assert_ne!(bytes(&1.0f64, v1), bytes(&-1.0f64, v1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn hash_collections_ignore_insertion_order() {
+    use std::collections::{HashMap, HashSet};
+    let v1 = Encoding::le(Version::V1);
+    let forward: HashMap<u32, String> = (0..100).map(|i| (i, i.to_string())).collect();
+    let mut backward = HashMap::with_capacity(1000);
+    for i in (0..100u32).rev() {
+        backward.insert(i, i.to_string());
+    }
+    assert_eq!(bytes(&forward, v1), bytes(&backward, v1));
+    let forward: HashSet<u32> = (0..100).collect();
+    let backward: HashSet<u32> = (0..100).rev().collect();
+    assert_eq!(bytes(&forward, v1), bytes(&backward, v1));
+    assert_ne!(bytes(&forward, v1), bytes(&HashSet::<u32>::new(), v1));
+}
+
+#[test]
+fn update_picks_the_byte_order() {
+    let mut le = ByteSink::default();
+    let mut be = ByteSink::default();
+    (0x0102u16, "a", 1.0f32).update_le(&mut le, Version::V1);
+    (0x0102u16, "a", 1.0f32).update_be(&mut be, Version::V1);
+    assert_eq!(
+        le.0,
+        vec![2, 1, 1, 0, 0, 0, 0, 0, 0, 0, b'a', 0, 0, 0x80, 0x3f]
+    );
+    assert_eq!(
+        be.0,
+        vec![1, 2, 0, 0, 0, 0, 0, 0, 0, 1, b'a', 0x3f, 0x80, 0, 0]
+    );
+}
+
+#[test]
+fn v0_digests_are_unchanged() {
+    // hashed with the unversioned impls the crate had before Version
+    let mut digest = Sha256::new();
+    0x0102u16.update_le(&mut digest, Version::V0);
+    Some(-7i32).update_le(&mut digest, Version::V0);
+    vec![String::from("ab"), String::from("c")].update_le(&mut digest, Version::V0);
+    [1u8, 2, 3].update_le(&mut digest, Version::V0);
+    assert_eq!(
+        digest.finalize()[..],
+        [
+            0xad, 0x26, 0x1d, 0x15, 0xda, 0x3e, 0x4b, 0x3a, 0x06, 0xab, 0xb6, 0x7b, 0xe9, 0xfe,
+            0x01, 0xc4, 0x2e, 0x67, 0x00, 0xb2, 0xba, 0x7c, 0x97, 0xf2, 0x93, 0x6e, 0xbd, 0xc9,
+            0x08, 0xf0, 0x7a, 0xd4
+        ]
+    );
+}
@@ -0,0 +1,69 @@
+use sha2::Sha256;
+use structured_digest::*;
+
+#[test]
+fn proofs_verify_every_leaf() {
+    let v1 = Encoding::le(Version::V1);
+    let values: Vec<u32> = (0..7).collect();
+    let root = values.merkle_root::<Sha256>(v1);
+    for (i, value) in values.iter().enumerate() {
+        let proof = merkle::prove_child::<Sha256, _>(&values, i, v1).unwrap();
+        assert!(proof.verify(merkle::leaf_hash::<Sha256, _>(value, v1), &root));
+        assert!(!proof.verify(merkle::leaf_hash::<Sha256, _>(&(value + 1), v1), &root));
+    }
+    assert!(merkle::prove_child::<Sha256, _>(&values, 7, v1).is_none());
+
+    let mut tampered = values.clone();
+    tampered[3] = 42;
+    let proof = merkle::prove_child::<Sha256, _>(&tampered, 3, v1).unwrap();
+    assert!(!proof.verify(merkle::leaf_hash::<Sha256, _>(&42u32, v1), &root));
+}
+
+#[test]
+fn leaves_are_not_nodes() {
+    let v1 = Encoding::le(Version::V1);
+    let hashes: Vec<_> = (0..2u8)
+        .map(|i| merkle::leaf_hash::<Sha256, _>(&i, v1))
+        .collect();
+    let root = merkle::node_hash::<Sha256>(&hashes[0], &hashes[1]);
+    assert_eq!(
+        merkle::root::<Sha256>(&hashes),
+        merkle::list_hash::<Sha256>(2, &root)
+    );
+    let mut joined = hashes[0].to_vec();
+    joined.extend_from_slice(&hashes[1]);
+    assert_ne!(
+        root,
+        merkle::leaf_hash::<Sha256, _>(&joined[..], Encoding::le(Version::V0))
+    );
+}
+
+#[test]
+fn roots_commit_to_nesting_and_length() {
+    let v1 = Encoding::le(Version::V1);
+    let pair = vec![vec![1u8, 2]];
+    let split = vec![vec![1u8], vec![2]];
+    assert_ne!(pair.merkle_root::<Sha256>(v1), split.merkle_root::<Sha256>(v1));
+
+    let empty: Vec<Vec<u8>> = vec![];
+    let nested_empty: Vec<Vec<u8>> = vec![vec![]];
+    assert_ne!(
+        empty.merkle_root::<Sha256>(v1),
+        nested_empty.merkle_root::<Sha256>(v1)
+    );
+}
+
+#[test]
+fn nested_proofs_round_trip() {
+    let v1 = Encoding::le(Version::V1);
+    let values = vec![vec![1u16, 2, 3], vec![4, 5]];
+    let inner = merkle::prove_child::<Sha256, _>(&values[1], 1, v1).unwrap();
+    let outer = merkle::prove_child::<Sha256, _>(&values, 1, v1).unwrap();
+    let proof = inner.then(outer);
+    let leaf = merkle::leaf_hash::<Sha256, _>(&5u16, v1);
+    assert!(proof.verify(leaf, &values.merkle_root::<Sha256>(v1)));
+
+    let mut bytes = ByteSink::default();
+    proof.digest(&mut bytes, v1);
+    assert_eq!(from_bytes::<MerkleProof<Sha256>>(&bytes.0, v1), Ok(proof));
+}
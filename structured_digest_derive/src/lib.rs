@@ -1,11 +1,394 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Expr, Field, Fields, GenericParam,
+    Generics, Ident, Index, LitStr, Path, Token, TypeParamBound,
+};
 
-#[proc_macro_derive(MyMacro)]
-pub fn my_macro(input: TokenStream) -> TokenStream {
-    let _input = parse_macro_input!(input as DeriveInput);
-    let expanded = quote! {
+enum FieldArg {
+    Skip,
+    With(Path),
+}
+
+impl Parse for FieldArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        if name == "skip" {
+            Ok(FieldArg::Skip)
+        } else if name == "with" {
+            input.parse::<Token![=]>()?;
+            if input.peek(LitStr) {
+                input.parse::<LitStr>()?.parse().map(FieldArg::With)
+            } else {
+                input.parse().map(FieldArg::With)
+            }
+        } else {
+            Err(syn::Error::new(name.span(), "unknown digest attribute"))
+        }
+    }
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    skip: bool,
+    with: Option<Path>,
+}
+
+fn field_options(field: &Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in field.attrs.iter().filter(|a| a.path.is_ident("digest")) {
+        let args =
+            attr.parse_args_with(Punctuated::<FieldArg, Token![,]>::parse_terminated)?;
+        for arg in args {
+            match arg {
+                FieldArg::Skip => options.skip = true,
+                FieldArg::With(p) => options.with = Some(p),
+            }
+        }
+    }
+    Ok(options)
+}
+
+enum ContainerArg {
+    Tag(LitStr),
+    Version(Box<Expr>),
+}
+
+impl Parse for ContainerArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        if name == "tag" {
+            input.parse().map(ContainerArg::Tag)
+        } else if name == "version" {
+            input.parse().map(ContainerArg::Version)
+        } else {
+            Err(syn::Error::new(name.span(), "unknown digest attribute"))
+        }
+    }
+}
+
+/// Types without `#[digest(tag = "...")]` have no type tag, renaming them can't change their
+/// digest. The schema version is written with the tag, so setting one requires a tag.
+fn container_options(input: &DeriveInput) -> syn::Result<(Option<LitStr>, Expr)> {
+    let mut tag = None;
+    let mut version = None;
+    for attr in input.attrs.iter().filter(|a| a.path.is_ident("digest")) {
+        let args =
+            attr.parse_args_with(Punctuated::<ContainerArg, Token![,]>::parse_terminated)?;
+        for arg in args {
+            match arg {
+                ContainerArg::Tag(t) => tag = Some(t),
+                ContainerArg::Version(v) => version = Some(*v),
+            }
+        }
+    }
+    match (&tag, version) {
+        (None, Some(v)) => Err(syn::Error::new_spanned(
+            v,
+            "a schema version needs a type tag, add `tag = \"...\"`",
+        )),
+        (_, v) => Ok((tag, v.unwrap_or_else(|| parse_quote!(0)))),
+    }
+}
+
+fn add_bounds(mut generics: Generics, bound: TypeParamBound) -> Generics {
+    for param in generics.params.iter_mut() {
+        if let GenericParam::Type(ref mut t) = param {
+            t.bounds.push(bound.clone());
+        }
+    }
+    generics
+}
+
+/// Emits one digest call per field, `access` yields the expression referencing the field.
+fn digest_fields(
+    fields: &Fields,
+    access: impl Fn(usize, &Field) -> TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let mut body = TokenStream2::new();
+    for (i, field) in fields.iter().enumerate() {
+        let options = field_options(field)?;
+        if options.skip {
+            continue;
+        }
+        let value = access(i, field);
+        body.extend(match options.with {
+            Some(path) => quote! { #path::digest(#value, sink, encoding); },
+            None => quote! { ::structured_digest::Digestable::digest(#value, sink, encoding); },
+        });
+    }
+    Ok(body)
+}
+
+fn binding(i: usize) -> Ident {
+    format_ident!("__field{}", i)
+}
+
+fn pattern(fields: &Fields) -> TokenStream2 {
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            let bindings = (0..named.named.len()).map(binding);
+            quote! { { #(#names: #bindings),* } }
+        }
+        Fields::Unnamed(unnamed) => {
+            let bindings = (0..unnamed.unnamed.len()).map(binding);
+            quote! { ( #(#bindings),* ) }
+        }
+        Fields::Unit => quote! {},
+    }
+}
+
+fn digest_body(data: &Data) -> syn::Result<TokenStream2> {
+    match data {
+        Data::Struct(s) => digest_fields(&s.fields, |i, f| match &f.ident {
+            Some(name) => quote! { &self.#name },
+            None => {
+                let index = Index::from(i);
+                quote! { &self.#index }
+            }
+        }),
+        Data::Enum(e) => {
+            let mut arms = TokenStream2::new();
+            for (discriminant, variant) in e.variants.iter().enumerate() {
+                let name = &variant.ident;
+                let pattern = pattern(&variant.fields);
+                let discriminant = discriminant as u32;
+                let fields = digest_fields(&variant.fields, |i, _| {
+                    let b = binding(i);
+                    quote! { #b }
+                })?;
+                arms.extend(quote! {
+                    #[allow(unused_variables)]
+                    Self::#name #pattern => {
+                        ::structured_digest::Digestable::digest(&#discriminant, sink, encoding);
+                        #fields
+                    }
+                });
+            }
+            Ok(quote! {
+                match self {
+                    #arms
+                }
+            })
+        }
+        Data::Union(u) => Err(syn::Error::new(
+            u.union_token.span,
+            "Digestable can not be derived for unions",
+        )),
+    }
+}
+
+/// Names of the fields `merkle_children` lists, tuple fields by their index.
+fn field_names(data: &Data) -> syn::Result<TokenStream2> {
+    let fields = match data {
+        Data::Struct(s) => &s.fields,
+        _ => return Ok(TokenStream2::new()),
+    };
+    let mut names = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if field_options(field)?.skip {
+            continue;
+        }
+        names.push(match &field.ident {
+            Some(name) => name.to_string(),
+            None => i.to_string(),
+        });
+    }
+    Ok(quote! {
+        const FIELDS: &'static [&'static str] = &[#(#names),*];
+    })
+}
+
+/// Structs are Merkle nodes over their fields, enums stay a single leaf.
+fn merkle_children(data: &Data) -> syn::Result<TokenStream2> {
+    let fields = match data {
+        Data::Struct(s) => &s.fields,
+        _ => return Ok(TokenStream2::new()),
     };
-    TokenStream::from(expanded)
+    let mut children = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let options = field_options(field)?;
+        if options.skip {
+            continue;
+        }
+        let value = match &field.ident {
+            Some(name) => quote! { &self.#name },
+            None => {
+                let index = Index::from(i);
+                quote! { &self.#index }
+            }
+        };
+        children.push(match options.with {
+            Some(path) => quote! {{
+                let mut d = ::structured_digest::merkle::leaf_hasher::<__D>();
+                #path::digest(#value, &mut d, encoding);
+                ::structured_digest::digest::Digest::finalize(d)
+            }},
+            None => quote! {
+                ::structured_digest::Digestable::merkle_root::<__D>(#value, encoding)
+            },
+        });
+    }
+    Ok(quote! {
+        ::structured_digest::__if_alloc! {
+            fn merkle_children<__D: ::structured_digest::digest::Digest>(
+                &self,
+                encoding: ::structured_digest::Encoding,
+            ) -> ::core::option::Option<
+                ::structured_digest::__private::Vec<::structured_digest::digest::Output<__D>>,
+            > {
+                let mut children: ::structured_digest::__private::Vec<_> =
+                    ::structured_digest::merkle::type_tag_leaf::<Self, __D>(encoding)
+                        .into_iter()
+                        .collect();
+                #(children.push(#children);)*
+                ::core::option::Option::Some(children)
+            }
+        }
+    })
+}
+
+fn expand_digestable(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = add_bounds(
+        input.generics.clone(),
+        parse_quote!(::structured_digest::Digestable),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (tag, version) = container_options(&input)?;
+    let body = digest_body(&input.data)?;
+    let merkle = merkle_children(&input.data)?;
+    let fields = field_names(&input.data)?;
+    let tag = match tag {
+        Some(tag) => quote! { ::core::option::Option::Some(#tag) },
+        None => quote! { ::core::option::Option::None },
+    };
+    Ok(quote! {
+        impl #impl_generics ::structured_digest::Digestable for #name #ty_generics #where_clause {
+            const TYPE_TAG: ::core::option::Option<&'static str> = #tag;
+            const SCHEMA_VERSION: u32 = #version;
+            #fields
+
+            #[allow(unused_variables)]
+            fn digest<__S: ::structured_digest::Sink + ?Sized>(
+                &self,
+                sink: &mut __S,
+                encoding: ::structured_digest::Encoding,
+            ) {
+                ::structured_digest::digest_type_tag::<Self, __S>(sink, encoding);
+                #body
+            }
+
+            #merkle
+        }
+    })
+}
+
+/// A schema version without a type tag would never be hashed, so it is rejected:
+///
+/// ```compile_fail
+/// use structured_digest::Digestable;
+///
+/// #[derive(Digestable)]
+/// #[digest(version = 1)]
+/// struct Untagged {
+///     value: u32,
+/// }
+/// ```
+#[proc_macro_derive(Digestable, attributes(digest))]
+pub fn derive_digestable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_digestable(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Builds the constructor arguments for `fields`, decoding them in declaration order. Paths
+/// given with `#[digest(with = ...)]` provide a `decode` function next to `digest`.
+fn decode_fields(fields: &Fields) -> syn::Result<TokenStream2> {
+    let mut values = Vec::new();
+    for field in fields.iter() {
+        let options = field_options(field)?;
+        values.push(if options.skip {
+            quote! { ::core::default::Default::default() }
+        } else if let Some(path) = options.with {
+            quote! { #path::decode(reader)? }
+        } else {
+            quote! { ::structured_digest::Decode::decode(reader)? }
+        });
+    }
+    Ok(match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { { #(#names: #values),* } }
+        }
+        Fields::Unnamed(_) => quote! { ( #(#values),* ) },
+        Fields::Unit => quote! {},
+    })
+}
+
+fn decode_body(data: &Data) -> syn::Result<TokenStream2> {
+    match data {
+        Data::Struct(s) => {
+            let fields = decode_fields(&s.fields)?;
+            Ok(quote! { ::core::result::Result::Ok(Self #fields) })
+        }
+        Data::Enum(e) => {
+            let mut arms = TokenStream2::new();
+            for (discriminant, variant) in e.variants.iter().enumerate() {
+                let name = &variant.ident;
+                let fields = decode_fields(&variant.fields)?;
+                let discriminant = discriminant as u32;
+                arms.extend(quote! {
+                    #discriminant => ::core::result::Result::Ok(Self::#name #fields),
+                });
+            }
+            Ok(quote! {
+                match <u32 as ::structured_digest::Decode>::decode(reader)? {
+                    #arms
+                    d => ::core::result::Result::Err(
+                        ::structured_digest::DecodeError::InvalidDiscriminant(d),
+                    ),
+                }
+            })
+        }
+        Data::Union(u) => Err(syn::Error::new(
+            u.union_token.span,
+            "Decode can not be derived for unions",
+        )),
+    }
+}
+
+fn expand_decode(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = add_bounds(
+        add_bounds(input.generics.clone(), parse_quote!(::structured_digest::Decode)),
+        parse_quote!(::structured_digest::Digestable),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = decode_body(&input.data)?;
+    Ok(quote! {
+        impl #impl_generics ::structured_digest::Decode for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn decode(
+                reader: &mut ::structured_digest::Reader<'_>,
+            ) -> ::core::result::Result<Self, ::structured_digest::DecodeError> {
+                ::structured_digest::decode_type_tag::<Self>(reader)?;
+                #body
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(Decode, attributes(digest))]
+pub fn derive_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decode(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
 }
@@ -0,0 +1,124 @@
+use structured_digest::{ByteSink, Digestable, Encoding, Version};
+
+fn bytes<T: Digestable>(value: &T) -> Vec<u8> {
+    bytes_with(value, Version::V1)
+}
+
+fn bytes_with<T: Digestable + ?Sized>(value: &T, version: Version) -> Vec<u8> {
+    let mut sink = ByteSink::default();
+    value.update_le(&mut sink, version);
+    sink.0
+}
+
+/// Writes only the low byte.
+mod low_byte {
+    use structured_digest::{Encoding, Sink};
+
+    pub fn digest<S: Sink + ?Sized>(value: &u32, sink: &mut S, _encoding: Encoding) {
+        sink.write(&[*value as u8])
+    }
+}
+
+#[derive(Digestable)]
+struct Tile {
+    x: i16,
+    #[digest(skip)]
+    #[allow(dead_code)]
+    cache: Vec<u8>,
+    #[digest(with = low_byte)]
+    color: u32,
+}
+
+#[derive(Digestable)]
+struct Pair(u8, u16);
+
+#[derive(Digestable)]
+enum Shape {
+    Dot,
+    Line(u8),
+    Box { w: u8, h: u8 },
+}
+
+#[derive(Digestable)]
+#[digest(tag = "test::Meters")]
+struct Meters {
+    value: u32,
+}
+
+#[derive(Digestable)]
+#[digest(tag = "test::Feet")]
+struct Feet {
+    value: u32,
+}
+
+#[derive(Digestable)]
+#[digest(tag = "test::Meters", version = 1)]
+struct MetersV1 {
+    value: u32,
+}
+
+#[test]
+fn skipped_fields_do_not_count() {
+    let tile = |cache| Tile {
+        x: 3,
+        cache,
+        color: 0x0102_0304,
+    };
+    assert_eq!(bytes(&tile(vec![])), bytes(&tile(vec![1, 2, 3])));
+    assert_eq!(bytes(&tile(vec![])), vec![3, 0, 4]);
+}
+
+#[test]
+fn with_replaces_the_field_encoding() {
+    let mut sink = ByteSink::default();
+    low_byte::digest(&0x1ff, &mut sink, Encoding::le(Version::V1));
+    assert_eq!(sink.0, vec![0xff]);
+    let tile = Tile {
+        x: -1,
+        cache: vec![],
+        color: 0x1ff,
+    };
+    assert_eq!(bytes(&tile), vec![0xff, 0xff, 0xff]);
+}
+
+#[test]
+fn tuple_structs_write_their_fields_in_order() {
+    assert_eq!(bytes(&Pair(1, 0x0302)), vec![1, 2, 3]);
+    assert_eq!(bytes(&Pair(1, 0x0302)), bytes(&(1u8, 0x0302u16)));
+}
+
+#[test]
+fn enums_start_with_the_variant() {
+    assert_eq!(bytes(&Shape::Dot), vec![0, 0, 0, 0]);
+    assert_eq!(bytes(&Shape::Line(7)), vec![1, 0, 0, 0, 7]);
+    assert_eq!(bytes(&Shape::Box { w: 7, h: 9 }), vec![2, 0, 0, 0, 7, 9]);
+    assert_ne!(bytes(&Shape::Line(0)), bytes(&Shape::Box { w: 0, h: 0 }));
+}
+
+#[test]
+fn type_tags_separate_equal_fields() {
+    let meters = bytes_with(&Meters { value: 5 }, Version::V2);
+    let feet = bytes_with(&Feet { value: 5 }, Version::V2);
+    let bumped = bytes_with(&MetersV1 { value: 5 }, Version::V2);
+    assert_ne!(meters, feet);
+    assert_ne!(meters, bumped);
+    assert_eq!(bytes(&Meters { value: 5 }), bytes(&Feet { value: 5 }));
+    assert_eq!(bytes(&Meters { value: 5 }), vec![5, 0, 0, 0]);
+
+    let mut tagged = bytes_with("test::Meters", Version::V2);
+    tagged.extend_from_slice(&[0, 0, 0, 0, 5, 0, 0, 0]);
+    assert_eq!(meters, tagged);
+    assert_eq!(MetersV1::SCHEMA_VERSION, 1);
+}
+
+#[test]
+fn untagged_types_keep_their_v1_bytes() {
+    assert_eq!(Pair::TYPE_TAG, None);
+    assert_eq!(bytes_with(&Pair(1, 2), Version::V2), bytes(&Pair(1, 2)));
+}
+
+#[test]
+fn fields_list_the_hashed_fields() {
+    assert_eq!(Tile::FIELDS, ["x", "color"]);
+    assert_eq!(Pair::FIELDS, ["0", "1"]);
+}